@@ -0,0 +1,57 @@
+// Times Path::past_light_cone_event on long recorded worldlines, run with `cargo bench`.
+// The lookup should stay roughly flat as the number of samples grows
+
+use bevy::prelude::*;
+use spacetime_game::{Interpolation, Path, Position, SuperluminalPolicy};
+use std::hint::black_box;
+use std::time::Instant;
+
+const SAMPLE_COUNTS: [usize; 4] = [100, 1_000, 10_000, 100_000];
+const LOOKUPS: usize = 100_000;
+
+// A slow spiral sampled every 1/60th of a second, like a recorded worldline
+fn recorded_path(samples: usize) -> Path {
+    Path::new(
+        (0..samples).map(|i| {
+            let time = i as f32 / 60.0;
+            (Vec2::new(time.cos(), time.sin()) * (100.0 + time), time)
+        }).collect(),
+        false,
+        Interpolation::Linear,
+        SuperluminalPolicy::Reject
+    ).expect("The spiral is timelike")
+}
+
+fn time_lookups(path: &Path, times: &[f64]) -> f64 {
+    let player_position = Position(Vec2::new(50.0, -20.0));
+    let start = Instant::now();
+
+    for &time in times {
+        black_box(path.past_light_cone_event(&player_position, black_box(time)));
+    }
+
+    start.elapsed().as_nanos() as f64 / times.len() as f64
+}
+
+fn main() {
+    println!("{:>10} {:>22} {:>22}", "samples", "sequential (ns/call)", "random (ns/call)");
+
+    for samples in SAMPLE_COUNTS {
+        let path = recorded_path(samples);
+        let duration = samples as f64 / 60.0;
+
+        // Frames marching forwards in time hit the cached index
+        let sequential: Vec<f64> = (0..LOOKUPS).map(|i| i as f64 * duration / LOOKUPS as f64).collect();
+
+        // Jumping around in time has to binary search every lookup
+        let mut state: u64 = 0x2545f4914f6cdd1d;
+        let random: Vec<f64> = (0..LOOKUPS).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 1_000_000) as f64 / 1_000_000.0 * duration
+        }).collect();
+
+        println!("{:>10} {:>22.1} {:>22.1}", samples, time_lookups(&path, &sequential), time_lookups(&path, &random));
+    }
+}
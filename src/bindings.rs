@@ -0,0 +1,388 @@
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, FileAssetIo, LoadContext, LoadedAsset},
+    input::gamepad::{GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads},
+    prelude::*,
+    reflect::TypeUuid
+};
+use serde::{Deserialize, Serialize};
+use std::{f32::consts::TAU, fmt};
+
+use super::*;
+
+// What the player can ask for and the keys, mouse buttons and gamepad buttons that ask for it,
+// read from a RON file in assets that is reloaded while the game runs, e.g.
+//
+//     cargo run -- --bindings input.bindings.ron
+//
+// Bindings are Key(..), Mouse(..) or Gamepad(..) with bevy's names for them, e.g.
+//
+//     (
+//         brake: [Key(Space), Key(LShift), Gamepad(South)],
+//         thrust_stick: Some(Left),
+//         dead_zone: 0.2,
+//     )
+//
+// Any action left out keeps its default. The thrust stick gives thrust in proportion to how far
+// it's pushed, on top of the keys, on whichever connected gamepad has it pushed furthest.
+//
+// The ship points where the mouse is from the player when it moves, or where the aim stick is
+// pushed, and can be turned with the turn bindings. The mouse doesn't aim while the scene is viewed
+// from another body's frame, see src/camera.rs.
+//
+// Forward and reverse thrust along where the ship points, on top of the thrust bindings that push
+// it along the axes. No mouse buttons are bound by default, so that clicking on the window doesn't
+// thrust. A binding like forward: [Mouse(Left)] adds one.
+//
+// Fire is read like the others, though nothing shoots yet
+
+const DEFAULT_BINDINGS_FILE: &str = "input.bindings.ron";
+
+// Reads the Actions every frame and fills in the player's input from them on every physics step.
+// Headless runs go without a file, keeping the default bindings that input scripts are written for
+pub struct BindingsPlugin {
+    // Relative to the assets folder
+    pub file: Option<String>,
+    pub live_reload: bool
+}
+
+impl BindingsPlugin {
+    // Reads --bindings <file>
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let file = args.find(|arg| arg == "--bindings").and_then(|_| args.next());
+
+        BindingsPlugin { file: Some(file.unwrap_or_else(|| DEFAULT_BINDINGS_FILE.to_string())), live_reload: true }
+    }
+}
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Actions>()
+            .add_system_to_stage(PhysicsStage::Step, read_actions.label(BindingsSystem::ReadActions))
+            .add_system_set_to_stage(PhysicsStage::Step, SystemSet::new()
+                .with_run_criteria(PhysicsSteps)
+                .with_system(read_player_input
+                    .label(BindingsSystem::ReadInput)
+                    .after(BindingsSystem::ReadActions)
+                    .before(PhysicsSystem::Move)));
+
+        let Some(file) = self.file.clone() else {
+            app.init_resource::<Bindings>();
+            return;
+        };
+
+        // Read straight away like the settings, see src/settings.rs
+        let path = FileAssetIo::get_base_path().join("assets").join(&file);
+
+        let bindings = match std::fs::read_to_string(&path) {
+            Ok(text) => match ron::from_str::<Bindings>(&text) {
+                Ok(bindings) => bindings,
+                Err(err) => {
+                    error!("Couldn't parse bindings file {:?}, using the defaults: {}", path, err);
+                    Bindings::default()
+                }
+            },
+            Err(err) => {
+                warn!("Couldn't read bindings file {:?}, using the defaults: {}", path, err);
+                Bindings::default()
+            }
+        };
+
+        app.insert_resource(bindings);
+
+        if self.live_reload {
+            app
+                .add_asset::<Bindings>()
+                .init_asset_loader::<BindingsLoader>()
+                .add_startup_system(move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    commands.insert_resource(BindingsHandle(asset_server.load(file.as_str())));
+                })
+                .add_system(reload_bindings);
+        }
+    }
+}
+
+#[derive(SystemLabel)]
+pub enum BindingsSystem {
+    // Once a frame, before any of the physics steps
+    ReadActions,
+    // On every physics step, for anything that replaces the player's input to run after
+    ReadInput
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource, Serialize, Deserialize, TypeUuid, Debug, Clone, PartialEq)]
+#[uuid = "5b7e2c90-1f4d-4c3a-8e61-d2a9b07f4c15"]
+#[serde(default)]
+pub struct Bindings {
+    pub thrust_up: Vec<Binding>,
+    pub thrust_down: Vec<Binding>,
+    pub thrust_left: Vec<Binding>,
+    pub thrust_right: Vec<Binding>,
+    pub brake: Vec<Binding>,
+    pub fire: Vec<Binding>,
+    pub pause: Vec<Binding>,
+    pub forward: Vec<Binding>,
+    pub reverse: Vec<Binding>,
+    pub turn_left: Vec<Binding>,
+    pub turn_right: Vec<Binding>,
+    pub thrust_stick: Option<Stick>,
+    pub aim_stick: Option<Stick>,
+    pub mouse_aim: bool,
+    // How far the stick has to be pushed, from 0 to 1, before it gives any thrust
+    pub dead_zone: f32
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stick {
+    Left,
+    Right
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::*;
+
+        Bindings {
+            thrust_up: vec![Key(KeyCode::W), Gamepad(GamepadButtonType::DPadUp)],
+            thrust_down: vec![Key(KeyCode::S), Gamepad(GamepadButtonType::DPadDown)],
+            thrust_left: vec![Key(KeyCode::A), Gamepad(GamepadButtonType::DPadLeft)],
+            thrust_right: vec![Key(KeyCode::D), Gamepad(GamepadButtonType::DPadRight)],
+            brake: vec![Key(KeyCode::Space), Gamepad(GamepadButtonType::South)],
+            fire: vec![Key(KeyCode::F), Gamepad(GamepadButtonType::RightTrigger)],
+            pause: vec![Key(KeyCode::F8), Gamepad(GamepadButtonType::Start)],
+            forward: vec![Key(KeyCode::T), Gamepad(GamepadButtonType::RightTrigger2)],
+            reverse: vec![Key(KeyCode::G), Gamepad(GamepadButtonType::LeftTrigger2)],
+            turn_left: vec![Key(KeyCode::Q)],
+            turn_right: vec![Key(KeyCode::E)],
+            thrust_stick: Some(Stick::Left),
+            aim_stick: Some(Stick::Right),
+            mouse_aim: true,
+            dead_zone: 0.15
+        }
+    }
+}
+
+impl Bindings {
+    // The bindings for an action by its name in the file, e.g. "thrust_right"
+    pub fn action(&self, name: &str) -> Option<&[Binding]> {
+        Some(match name {
+            "thrust_up" => &self.thrust_up,
+            "thrust_down" => &self.thrust_down,
+            "thrust_left" => &self.thrust_left,
+            "thrust_right" => &self.thrust_right,
+            "brake" => &self.brake,
+            "fire" => &self.fire,
+            "pause" => &self.pause,
+            "forward" => &self.forward,
+            "reverse" => &self.reverse,
+            "turn_left" => &self.turn_left,
+            "turn_right" => &self.turn_right,
+            _ => return None
+        })
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(button) => write!(f, "{:?} mouse button", button),
+            Binding::Gamepad(button) => write!(f, "{:?} on the gamepad", button)
+        }
+    }
+}
+
+impl Stick {
+    fn axes(&self) -> (GamepadAxisType, GamepadAxisType) {
+        match self {
+            Stick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            Stick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY)
+        }
+    }
+}
+
+// What the player is asking for this frame, whatever it came from
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct Actions {
+    // From -1 to 1 on each axis
+    pub thrust: Vec2,
+    pub brake: bool,
+    pub fire: bool,
+    // Only on the frame it's pressed
+    pub pause: bool,
+    // Along the ship's heading, from -1 to 1
+    pub forward: f32,
+    // Radians anticlockwise from +x, when the mouse or aim stick says where to point. Kept until a
+    // physics step takes it, so that frames without one don't lose where the mouse moved to
+    pub aim: Option<f32>,
+    // From -1 (clockwise) to 1
+    pub turn: f32
+}
+
+#[derive(Resource)]
+struct BindingsHandle(Handle<Bindings>);
+
+// ----------------------------------<< Loaders >>----------------------------------
+
+#[derive(Default)]
+pub struct BindingsLoader;
+
+impl AssetLoader for BindingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let bindings: Bindings = ron::de::from_bytes(bytes)?;
+
+            load_context.set_default_asset(LoadedAsset::new(bindings));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["bindings.ron"]
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// Runs once a frame, before the physics steps. Headless runs only have the keyboard, the other
+// devices are read when there are any. The mouse is left to the editor while it's open
+#[allow(clippy::too_many_arguments)]
+pub fn read_actions(
+    bindings: Res<Bindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Option<Res<Input<MouseButton>>>,
+    gamepad_input: Option<Res<Input<GamepadButton>>>,
+    gamepad_axes: Option<Res<Axis<GamepadAxis>>>,
+    gamepads: Option<Res<Gamepads>>,
+    windows: Option<Res<Windows>>,
+    editor: Option<Res<Editor>>,
+    view_frame: Option<Res<ViewFrame>>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    mut last_cursor: Local<Option<Vec2>>,
+    mut actions: ResMut<Actions>
+) {
+    let mouse_input = mouse_input.filter(|_| !editor.as_ref().is_some_and(|editor| editor.active));
+
+    // The button on every connected gamepad
+    let gamepad_buttons = |button: GamepadButtonType| gamepads.iter()
+        .flat_map(|gamepads| gamepads.iter())
+        .map(move |gamepad| GamepadButton::new(gamepad, button));
+
+    let pressed = |binding: &Binding| match binding {
+        Binding::Key(key) => keyboard_input.pressed(*key),
+        Binding::Mouse(button) => mouse_input.as_ref().is_some_and(|input| input.pressed(*button)),
+        Binding::Gamepad(button) => gamepad_input.as_ref().is_some_and(|input| gamepad_buttons(*button).any(|button| input.pressed(button)))
+    };
+
+    let just_pressed = |binding: &Binding| match binding {
+        Binding::Key(key) => keyboard_input.just_pressed(*key),
+        Binding::Mouse(button) => mouse_input.as_ref().is_some_and(|input| input.just_pressed(*button)),
+        Binding::Gamepad(button) => gamepad_input.as_ref().is_some_and(|input| gamepad_buttons(*button).any(|button| input.just_pressed(button)))
+    };
+
+    let held = |bindings: &[Binding]| bindings.iter().any(pressed);
+    let axis = |positive: &[Binding], negative: &[Binding]| (held(positive) as i32 - held(negative) as i32) as f32;
+
+    let keys = Vec2::new(axis(&bindings.thrust_right, &bindings.thrust_left), axis(&bindings.thrust_up, &bindings.thrust_down));
+
+    // The one pushed furthest across the connected gamepads, rescaled past the dead zone so that
+    // it still starts from nothing
+    let stick = |stick: Option<Stick>| -> Vec2 {
+        let (Some(stick), Some(gamepad_axes), Some(gamepads)) = (stick, &gamepad_axes, &gamepads) else { return Vec2::ZERO };
+        let (x, y) = stick.axes();
+
+        let stick = gamepads.iter()
+            .map(|gamepad| Vec2::new(
+                gamepad_axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.0),
+                gamepad_axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.0)
+            ))
+            .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+            .unwrap_or(Vec2::ZERO);
+
+        let dead_zone = bindings.dead_zone.clamp(0.0, 0.99);
+        let pushed = stick.length().min(1.0);
+
+        if pushed <= dead_zone { Vec2::ZERO } else { stick.normalize() * (pushed - dead_zone) / (1.0 - dead_zone) }
+    };
+
+    // Only when it moves on the window, so the keys can turn the ship while the mouse is still.
+    // The heading is measured in the player's rest frame, which the scene is only drawn in while
+    // it's viewed from the player's own frame. Another body's frame is boosted and so squashed
+    // differently, where the cursor's angle isn't one the ship can point at
+    let own_frame = view_frame.as_ref().is_none_or(|view_frame| view_frame.body.is_none());
+
+    let window = windows.as_ref()
+        .and_then(|windows| windows.get_primary())
+        .filter(|_| bindings.mouse_aim && mouse_input.is_some() && own_frame);
+
+    let mouse_aim = match (window, query_camera.get_single()) {
+        (Some(window), Ok((camera, projection))) if window.cursor_position().is_some_and(|cursor| *last_cursor != Some(cursor)) => {
+            *last_cursor = window.cursor_position();
+
+            cursor_in_view(window, camera, projection)
+                .filter(|cursor| *cursor != Vec2::ZERO)
+                .map(|cursor| cursor.y.atan2(cursor.x).rem_euclid(TAU))
+        },
+        _ => None
+    };
+
+    let aim_stick = stick(bindings.aim_stick);
+    let stick_aim = (aim_stick != Vec2::ZERO).then(|| aim_stick.y.atan2(aim_stick.x).rem_euclid(TAU));
+
+    actions.thrust = (keys + stick(bindings.thrust_stick)).clamp(Vec2::NEG_ONE, Vec2::ONE);
+    actions.brake = held(&bindings.brake);
+    actions.fire = held(&bindings.fire);
+    actions.pause = bindings.pause.iter().any(just_pressed);
+    actions.forward = axis(&bindings.forward, &bindings.reverse);
+    actions.turn = axis(&bindings.turn_left, &bindings.turn_right);
+
+    if let Some(aim) = stick_aim.or(mouse_aim) {
+        actions.aim = Some(aim);
+    }
+}
+
+fn read_player_input(mut actions: ResMut<Actions>, mut player_input: ResMut<PlayerInput>) {
+    *player_input = PlayerInput {
+        thrust: actions.thrust,
+        brake: actions.brake,
+        forward: actions.forward,
+        aim: actions.aim.take(),
+        turn: actions.turn
+    };
+}
+
+fn reload_bindings(
+    mut asset_events: EventReader<AssetEvent<Bindings>>,
+    bindings_assets: Res<Assets<Bindings>>,
+    bindings_handle: Option<Res<BindingsHandle>>,
+    mut bindings: ResMut<Bindings>
+) {
+    let Some(bindings_handle) = bindings_handle else { return };
+
+    for event in asset_events.iter() {
+        let AssetEvent::Modified { handle } = event else { continue };
+        if *handle != bindings_handle.0 {
+            continue;
+        }
+
+        if let Some(reloaded) = bindings_assets.get(handle) {
+            if *bindings != *reloaded {
+                info!("Reloaded bindings");
+                *bindings = reloaded.clone();
+            }
+        }
+    }
+}
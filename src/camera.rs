@@ -0,0 +1,257 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    transform::TransformSystem
+};
+
+use super::*;
+
+// Zooms and moves the camera, and picks the frame the scene is drawn in. The camera can follow the
+// player, follow the body whose frame is being viewed from, or be dragged around freely. Viewing
+// from a body's frame keeps the player's event (what's seen doesn't change) but takes the body's
+// velocity for the boost, see ViewFrame
+//
+// Controls:
+//   Scroll wheel  zoom in/out
+//   Middle drag   pan, letting go of whatever the camera was following
+//   C             follow the player, the viewed body, or nothing
+//   V             view from the next body's frame, Shift for the previous one
+//   Z             back to the player's frame, following it at normal zoom
+
+const CAMERA_MIN_ZOOM: f32 = 0.1;
+const CAMERA_MAX_ZOOM: f32 = 10.0;
+// How much one line of scrolling zooms by
+const CAMERA_ZOOM_STEP: f32 = 1.1;
+// Touchpads scroll in pixels rather than lines
+const CAMERA_PIXELS_PER_LINE: f32 = 20.0;
+
+const CAMERA_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const CAMERA_FONT_SIZE: f32 = 16.0;
+const CAMERA_MARGIN: f32 = 10.0;
+
+const CAMERA_COLOR: Color = Color::rgb(150.0 / 256.0, 210.0 / 256.0, 255.0 / 256.0 );
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CameraView>()
+            .add_startup_system(setup_camera)
+            .add_system(control_camera)
+            .add_system(draw_camera.after(control_camera))
+            // Once the reorient systems have put the bodies where they're seen
+            .add_system_to_stage(CoreStage::PostUpdate, move_camera.label(CameraSystem::Move).before(TransformSystem::TransformPropagate));
+    }
+}
+
+#[derive(SystemLabel)]
+pub enum CameraSystem {
+    // In PostUpdate, anything kept in place on the window goes after this, see overlay_transform
+    Move
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct CameraView {
+    pub follow: Follow,
+    // Above 1 is zoomed in
+    pub zoom: f32,
+    // Where in the world the middle of the window is while following nothing, so the player can
+    // fly out of sight
+    pub center: Vec2
+}
+
+impl Default for CameraView {
+    fn default() -> Self {
+        CameraView { follow: Follow::Player, zoom: 1.0, center: Vec2::ZERO }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Follow {
+    Player,
+    // The body in ViewFrame, or the player while there isn't one
+    Body,
+    Free
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct CameraText;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup_camera(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((TextBundle::from_section("", TextStyle {
+        font: asset_server.load(CAMERA_FONT),
+        font_size: CAMERA_FONT_SIZE,
+        color: CAMERA_COLOR
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            bottom: Val::Px(CAMERA_MARGIN),
+            right: Val::Px(CAMERA_MARGIN),
+            ..default()
+        },
+        ..default()
+    }), CameraText));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn control_camera(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    windows: Res<Windows>,
+    rendered_player: Res<RenderedPlayer>,
+    mut last_cursor: Local<Option<Vec2>>,
+    mut camera_view: ResMut<CameraView>,
+    mut view_frame: ResMut<ViewFrame>,
+    query_bodies: Query<Entity, With<LevelBody>>,
+    query_camera: Query<&Transform, With<Camera2d>>
+) {
+    for event in mouse_wheel.iter() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / CAMERA_PIXELS_PER_LINE
+        };
+
+        camera_view.zoom = (camera_view.zoom * CAMERA_ZOOM_STEP.powf(lines)).clamp(CAMERA_MIN_ZOOM, CAMERA_MAX_ZOOM);
+    }
+
+    // Dragging the scene along with the cursor
+    let cursor = windows.get_primary().and_then(|window| window.cursor_position());
+
+    if mouse_input.pressed(MouseButton::Middle) {
+        if let (Some(cursor), Some(last_cursor)) = (cursor, *last_cursor) {
+            if cursor != last_cursor {
+                if camera_view.follow != Follow::Free {
+                    camera_view.follow = Follow::Free;
+                    camera_view.center = rendered_player.0.position + query_camera.get_single().map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+                }
+
+                let zoom = camera_view.zoom;
+                camera_view.center -= (cursor - last_cursor) / zoom;
+            }
+        }
+
+        *last_cursor = cursor;
+    } else {
+        *last_cursor = None;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::C) {
+        camera_view.follow = match camera_view.follow {
+            Follow::Player => Follow::Body,
+            Follow::Body => Follow::Free,
+            Follow::Free => Follow::Player
+        };
+    }
+
+    // In the order they were spawned, with the player's own frame between the last and the first
+    if keyboard_input.just_pressed(KeyCode::V) {
+        let mut bodies: Vec<Entity> = query_bodies.iter().collect();
+        bodies.sort();
+
+        let current = view_frame.body.and_then(|body| bodies.iter().position(|&other| other == body));
+        let backwards = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+        let next = match (current, backwards) {
+            (None, false) => 0,
+            (None, true) => bodies.len().wrapping_sub(1),
+            (Some(current), false) => current + 1,
+            (Some(current), true) => current.wrapping_sub(1)
+        };
+
+        view_frame.body = bodies.get(next).copied();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Z) {
+        *camera_view = CameraView::default();
+        view_frame.body = None;
+    }
+}
+
+// The player is always drawn at the origin, so following it is staying there
+fn move_camera(
+    camera_view: Res<CameraView>,
+    rendered_player: Res<RenderedPlayer>,
+    view_frame: Res<ViewFrame>,
+    query_bodies: Query<&Transform, (With<LevelBody>, Without<Camera2d>)>,
+    mut query_camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>
+) {
+    let Ok((mut transform, mut projection)) = query_camera.get_single_mut() else { return };
+
+    let center = match camera_view.follow {
+        Follow::Player => Vec2::ZERO,
+        Follow::Body => view_frame.body
+            .and_then(|body| query_bodies.get(body).ok())
+            .map_or(Vec2::ZERO, |body| body.translation.truncate()),
+        Follow::Free => camera_view.center - rendered_player.0.position
+    };
+
+    if transform.translation.truncate() != center {
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+
+    if projection.scale != 1.0 / camera_view.zoom {
+        projection.scale = 1.0 / camera_view.zoom;
+    }
+}
+
+// Only says anything once the view isn't the usual one
+fn draw_camera(
+    camera_view: Res<CameraView>,
+    view_frame: Res<ViewFrame>,
+    query_names: Query<Option<&Name>, With<LevelBody>>,
+    mut query_text: Query<(&mut Text, &mut Visibility), With<CameraText>>
+) {
+    let (mut text, mut visibility) = query_text.single_mut();
+
+    let usual = camera_view.follow == Follow::Player && camera_view.zoom == 1.0 && view_frame.body.is_none();
+    if visibility.is_visible == usual {
+        visibility.is_visible = !usual;
+    }
+
+    if usual {
+        return;
+    }
+
+    let frame = match view_frame.body.map(|body| query_names.get(body)) {
+        Some(Ok(Some(name))) => name.to_string(),
+        Some(_) => "an unnamed body".to_string(),
+        None => "the player".to_string()
+    };
+
+    let follow = match camera_view.follow {
+        Follow::Player => "the player",
+        Follow::Body if view_frame.body.is_some() => "the body",
+        Follow::Body => "the player",
+        Follow::Free => "nothing"
+    };
+
+    text.sections[0].value = format!(
+        "Viewing from the frame of {}, following {}, zoom {:.2}x\nV/Shift+V frame, C follow, middle drag pan, Z reset",
+        frame,
+        follow,
+        camera_view.zoom
+    );
+}
+
+// For something drawn `offset` pixels from the middle of the window, scaled so that it keeps its
+// size on the window however far the camera is zoomed
+pub fn overlay_transform(camera: &Transform, projection: &OrthographicProjection, offset: Vec2, depth: f32) -> Transform {
+    Transform::from_translation((camera.translation.truncate() + offset * projection.scale).extend(depth))
+        .with_scale(Vec3::new(projection.scale, projection.scale, 1.0))
+}
+
+// Where the cursor is in the scene as drawn, relative to the player, if it's over the window
+pub fn cursor_in_view(window: &Window, camera: &Transform, projection: &OrthographicProjection) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+
+    Some((cursor - Vec2::new(window.width(), window.height()) / 2.0) * projection.scale + camera.translation.truncate())
+}
@@ -0,0 +1,179 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle, transform::TransformSystem};
+use std::f32::consts::TAU;
+
+use super::*;
+
+// Clock faces are drawn next to the body they belong to rather than as its children, so that they
+// don't inherit the body's scale
+const CLOCK_FACE_RADIUS: f32 = 14.0;
+const CLOCK_FACE_OFFSET: Vec2 = Vec2::new(20.0, 20.0);
+const CLOCK_FACE_TICKS: usize = 12;
+const CLOCK_FACE_TICK_LENGTH: f32 = 3.0;
+const CLOCK_FACE_SEGMENTS: usize = 32;
+
+const CLOCK_FACE_COLOR: Color = Color::rgb(170.0 / 256.0, 170.0 / 256.0, 190.0 / 256.0 );
+const CLOCK_HAND_COLOR: Color = Color::rgb(255.0 / 256.0, 255.0 / 256.0, 255.0 / 256.0 );
+
+const CLOCK_RENDER_DEPTH: f32 = 5.0;
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_system(spawn_clock_faces)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_clock_faces.before(TransformSystem::TransformPropagate)
+            );
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+// Put on a body with a Path or AnalyticPath, or on a Point, to show a clock next to it. The clock
+// reads the body's proper time at the event the player is seeing, so it runs slow when the body is
+// moving and fast or slow as it comes towards or goes away from the player
+#[derive(Component)]
+pub struct ClockFace {
+    // Seconds of proper time for the hand to go all the way around
+    pub period: f32
+}
+
+// The face drawn for a body with a ClockFace
+#[derive(Component)]
+struct ClockFaceOf(Entity);
+
+#[derive(Component)]
+struct ClockHand;
+
+// How a body moves, whichever components it has that say so
+pub enum BodyMotion<'a> {
+    Sampled(&'a Path),
+    Analytic(&'a dyn Trajectory),
+    // A Point, sitting still at its Position with a clock that keeps coordinate time
+    AtRest(Vec2)
+}
+
+impl<'a> BodyMotion<'a> {
+    // A Path or AnalyticPath takes precedence over a Position
+    pub fn new(path: Option<&'a Path>, analytic_path: Option<&'a AnalyticPath>, position: Option<&Position>) -> Option<Self> {
+        match (path, analytic_path, position) {
+            (Some(path), _, _) => Some(BodyMotion::Sampled(path)),
+            (None, Some(AnalyticPath(trajectory)), _) => Some(BodyMotion::Analytic(trajectory.as_ref())),
+            (None, None, Some(Position(position))) => Some(BodyMotion::AtRest(*position)),
+            (None, None, None) => None
+        }
+    }
+
+    // The body's proper time at the event on the player's past light cone, if it can be seen
+    pub fn seen_proper_time(&self, player_position: &Position, global_time: f64) -> Option<f64> {
+        match self {
+            BodyMotion::Sampled(path) => match path.past_light_cone_event(player_position, global_time) {
+                Sighting::At(_, time) => Some(path.proper_time_at(time / SPEED_OF_LIGHT as f64)),
+                Sighting::BeforeStart | Sighting::AfterEnd => None
+            },
+            BodyMotion::Analytic(trajectory) => trajectory
+                .past_light_cone_time(player_position.0, global_time)
+                .map(|time| trajectory.proper_time(time)),
+            BodyMotion::AtRest(position) => Some(global_time - (position.distance(player_position.0) / SPEED_OF_LIGHT) as f64)
+        }
+    }
+
+    // Where the body is and what its clock reads at a coordinate time
+    pub fn event(&self, time: f64) -> (Vec2, f64) {
+        match self {
+            BodyMotion::Sampled(path) => (path.position_at(time), path.proper_time_at(time)),
+            BodyMotion::Analytic(trajectory) => (trajectory.position(time), trajectory.proper_time(time)),
+            BodyMotion::AtRest(position) => (*position, time)
+        }
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn spawn_clock_faces(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query_bodies: Query<Entity, Added<ClockFace>>
+) {
+    for body in query_bodies.iter() {
+        let mut face = Vec::new();
+
+        for i in 0..CLOCK_FACE_SEGMENTS {
+            let angle = |i: usize| i as f32 * TAU / CLOCK_FACE_SEGMENTS as f32;
+            face.push((
+                CLOCK_FACE_RADIUS * Vec2::from_angle(angle(i)),
+                CLOCK_FACE_RADIUS * Vec2::from_angle(angle(i + 1)),
+                CLOCK_FACE_COLOR
+            ));
+        }
+
+        for i in 0..CLOCK_FACE_TICKS {
+            let direction = Vec2::from_angle(i as f32 * TAU / CLOCK_FACE_TICKS as f32);
+            face.push((
+                (CLOCK_FACE_RADIUS - CLOCK_FACE_TICK_LENGTH) * direction,
+                CLOCK_FACE_RADIUS * direction,
+                CLOCK_FACE_COLOR
+            ));
+        }
+
+        // Drawn pointing up, at 12 o'clock
+        let hand = vec![(Vec2::ZERO, Vec2::new(0.0, CLOCK_FACE_RADIUS - CLOCK_FACE_TICK_LENGTH), CLOCK_HAND_COLOR)];
+
+        commands.spawn((MaterialMesh2dBundle {
+            mesh: meshes.add(line_mesh(face)).into(),
+            material: materials.add(ColorMaterial::default()),
+            transform: Transform::from_translation(Vec3::new(f32::MAX, f32::MAX, CLOCK_RENDER_DEPTH)),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        }, ClockFaceOf(body))).with_children(|parent| {
+            parent.spawn((MaterialMesh2dBundle {
+                mesh: meshes.add(line_mesh(hand)).into(),
+                material: materials.add(ColorMaterial::default()),
+                transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.01)),
+                ..default()
+            }, ClockHand));
+        });
+    }
+}
+
+// Moves each face next to where its body is drawn and turns its hand to the proper time the
+// player sees. Runs after the bodies are reoriented so the faces don't lag a frame behind
+#[allow(clippy::type_complexity)]
+fn update_clock_faces(
+    mut commands: Commands,
+    rendered_player: Res<RenderedPlayer>,
+    query_bodies: Query<(&ClockFace, &Transform, &Visibility, Option<&Path>, Option<&AnalyticPath>, Option<&Position>), Without<ClockFaceOf>>,
+    mut query_faces: Query<(Entity, &ClockFaceOf, &mut Transform, &mut Visibility, &Children), Without<ClockHand>>,
+    mut query_hands: Query<&mut Transform, (With<ClockHand>, Without<ClockFace>)>
+) {
+    let player_position = &Position(rendered_player.0.position);
+    let global_time = rendered_player.0.global_time as f64;
+
+    for (face_entity, ClockFaceOf(body), mut face_transform, mut face_visibility, children) in query_faces.iter_mut() {
+        let Ok((clock_face, body_transform, body_visibility, path, analytic_path, position)) = query_bodies.get(*body) else {
+            commands.entity(face_entity).despawn_recursive();
+            continue;
+        };
+
+        let proper_time = BodyMotion::new(path, analytic_path, position)
+            .and_then(|motion| motion.seen_proper_time(player_position, global_time));
+
+        face_visibility.is_visible = body_visibility.is_visible && proper_time.is_some();
+        let Some(proper_time) = proper_time else { continue };
+
+        face_transform.translation.x = body_transform.translation.x + CLOCK_FACE_OFFSET.x;
+        face_transform.translation.y = body_transform.translation.y + CLOCK_FACE_OFFSET.y;
+
+        // Clockwise from 12 o'clock
+        let turns = (proper_time / clock_face.period as f64).rem_euclid(1.0) as f32;
+
+        for child in children.iter() {
+            if let Ok(mut hand_transform) = query_hands.get_mut(*child) {
+                hand_transform.rotation = Quat::from_rotation_z(-TAU * turns);
+            }
+        }
+    }
+}
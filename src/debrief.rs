@@ -0,0 +1,218 @@
+use bevy::{prelude::*, sprite::{MaterialMesh2dBundle, Mesh2dHandle}, transform::TransformSystem};
+use std::fmt::Write;
+
+use super::*;
+
+// Once every objective in a level with `compare_with` is done, shows the player's clock next to
+// that body's and a spacetime diagram of both worldlines since the level started. Distance is
+// measured from the body along the direction the player got furthest from it, and each
+// worldline is ticked every DEBRIEF_TICK_INTERVAL of its own proper time, so the one that aged
+// less has fewer ticks
+
+const DEBRIEF_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const DEBRIEF_FONT_SIZE: f32 = 16.0;
+
+const DEBRIEF_DIAGRAM_SIZE: Vec2 = Vec2::new(360.0, 260.0);
+const DEBRIEF_MARGIN: f32 = 20.0;
+const DEBRIEF_MAX_SEGMENTS: usize = 400; // per worldline
+const DEBRIEF_TICK_INTERVAL: f32 = 5.0;
+const DEBRIEF_TICK_SIZE: f32 = 5.0;
+
+const DEBRIEF_COLOR: Color = Color::rgb(220.0 / 256.0, 220.0 / 256.0, 235.0 / 256.0 );
+const DEBRIEF_PLAYER_COLOR: Color = Color::rgb(255.0 / 256.0, 195.0 / 256.0, 0.0 / 256.0 );
+const DEBRIEF_BODY_COLOR: Color = Color::rgb(90.0 / 256.0, 190.0 / 256.0, 255.0 / 256.0 );
+const DEBRIEF_AXIS_COLOR: Color = Color::rgb(110.0 / 256.0, 110.0 / 256.0, 130.0 / 256.0 );
+const DEBRIEF_DIAGRAM_COLOR: Color = Color::rgba(10.0 / 256.0, 10.0 / 256.0, 25.0 / 256.0, 0.85);
+
+const DEBRIEF_RENDER_DEPTH: f32 = 10.0;
+
+pub struct DebriefPlugin;
+
+impl Plugin for DebriefPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_startup_system(setup_debrief)
+            .add_system(show_debrief)
+            // Once the camera has moved, so it doesn't trail a frame behind
+            .add_system_to_stage(CoreStage::PostUpdate, place_debrief_diagram
+                .after(CameraSystem::Move)
+                .before(TransformSystem::TransformPropagate));
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct DebriefText;
+
+#[derive(Component)]
+struct DebriefDiagram;
+
+#[derive(Component)]
+struct DebriefDiagramBackground;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup_debrief(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    asset_server: Res<AssetServer>
+) {
+    commands.spawn((TextBundle::from_section("", TextStyle {
+        font: asset_server.load(DEBRIEF_FONT),
+        font_size: DEBRIEF_FONT_SIZE,
+        color: DEBRIEF_COLOR
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            bottom: Val::Px(2.0 * DEBRIEF_MARGIN + DEBRIEF_DIAGRAM_SIZE.y),
+            right: Val::Px(DEBRIEF_MARGIN),
+            ..default()
+        },
+        ..default()
+    }), DebriefText));
+
+    commands.spawn((MaterialMesh2dBundle {
+        mesh: meshes.add(shape::Quad::new(DEBRIEF_DIAGRAM_SIZE).into()).into(),
+        material: materials.add(ColorMaterial::from(DEBRIEF_DIAGRAM_COLOR)),
+        visibility: Visibility::INVISIBLE,
+        ..default()
+    }, DebriefDiagramBackground));
+
+    commands.spawn((MaterialMesh2dBundle {
+        mesh: meshes.add(line_mesh(Vec::new())).into(),
+        material: materials.add(ColorMaterial::from(Color::WHITE)),
+        visibility: Visibility::INVISIBLE,
+        ..default()
+    }, DebriefDiagram));
+}
+
+// Takes the comparison when the objectives are all done, and clears it when the level restarts
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn show_debrief(
+    objectives: Res<Objectives>,
+    current_level: Option<Res<CurrentLevel>>,
+    worldline: Res<Worldline>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut shown: Local<bool>,
+    query_bodies: Query<(&Name, Option<&Path>, Option<&AnalyticPath>, Option<&Position>)>,
+    mut query_text: Query<&mut Text, With<DebriefText>>,
+    mut query_diagram: Query<(&mut Visibility, &Mesh2dHandle), With<DebriefDiagram>>,
+    mut query_background: Query<&mut Visibility, (With<DebriefDiagramBackground>, Without<DebriefDiagram>)>
+) {
+    let complete = objectives.all_complete();
+
+    if complete == *shown {
+        return;
+    }
+
+    let text = &mut query_text.single_mut().sections[0].value;
+    let (mut diagram_visibility, diagram_mesh) = query_diagram.single_mut();
+    let mut background_visibility = query_background.single_mut();
+
+    text.clear();
+    diagram_visibility.is_visible = false;
+    background_visibility.is_visible = false;
+    *shown = complete;
+
+    let Some(name) = current_level.as_ref().and_then(|level| level.asset.compare_with.as_ref()) else { return };
+    let (Some(end), true) = (worldline.0.last(), complete) else { return };
+
+    let motion = query_bodies.iter()
+        .find(|(body_name, ..)| body_name.as_str() == name)
+        .and_then(|(_, path, analytic_path, position)| BodyMotion::new(path, analytic_path, position));
+
+    let Some(motion) = motion else {
+        warn!("There's no body called {:?} to compare clocks with", name);
+        return;
+    };
+
+    let body_at = |time: f32| {
+        let (position, proper_time) = motion.event(time as f64);
+        (position, proper_time as f32)
+    };
+    let (_, body_end_time) = body_at(end.global_time);
+    let (_, body_start_time) = body_at(worldline.0[0].global_time);
+
+    let player_aged = end.local_time - worldline.0[0].local_time;
+    let body_aged = body_end_time - body_start_time;
+
+    let _ = writeln!(text, "Back together at coordinate time {:.2}s", end.global_time);
+    let _ = writeln!(text, "Your clock        {:>9.2}s", player_aged);
+    let _ = writeln!(text, "{:<17} {:>9.2}s", format!("{}'s clock", name), body_aged);
+    let _ = writeln!(text, "You are {:.2}s {} than {}", (body_aged - player_aged).abs(), if player_aged < body_aged { "younger" } else { "older" }, name);
+
+    // Both worldlines as (distance along the axis, coordinate time, proper time)
+    let step = (worldline.0.len() / DEBRIEF_MAX_SEGMENTS).max(1);
+    let player_events: Vec<(Vec2, f32, f32)> = worldline.0.iter().step_by(step).chain(std::iter::once(end))
+        .map(|state| (state.position, state.global_time, state.local_time))
+        .collect();
+
+    let body_events: Vec<(Vec2, f32, f32)> = (0..=DEBRIEF_MAX_SEGMENTS).map(|i| {
+        let time = worldline.0[0].global_time + (end.global_time - worldline.0[0].global_time) * i as f32 / DEBRIEF_MAX_SEGMENTS as f32;
+        let (position, proper_time) = body_at(time);
+        (position, time, proper_time)
+    }).collect();
+
+    let origin = body_events[0].0;
+    let axis = player_events.iter()
+        .map(|(position, time, _)| *position - body_at(*time).0)
+        .max_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .map_or(Vec2::X, |furthest| furthest.try_normalize().unwrap_or(Vec2::X));
+
+    let to_plane = |events: &[(Vec2, f32, f32)]| events.iter()
+        .map(|(position, time, proper_time)| (Vec2::new((*position - origin).dot(axis), *time), *proper_time))
+        .collect::<Vec<_>>();
+    let player_events = to_plane(&player_events);
+    let body_events = to_plane(&body_events);
+
+    // Scaled to fit, with the same margin on every side
+    let (min, max) = player_events.iter().chain(body_events.iter()).fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), (point, _)| (min.min(*point), max.max(*point))
+    );
+    let size = (max - min).max(Vec2::new(100.0, 1.0));
+    let min = (min + max) / 2.0 - size * 0.55;
+    let max = min + size * 1.1;
+    let to_diagram = |point: Vec2| (point - min) / (max - min) * DEBRIEF_DIAGRAM_SIZE;
+
+    let mut lines = vec![
+        (to_diagram(Vec2::new(0.0, min.y)), to_diagram(Vec2::new(0.0, max.y)), DEBRIEF_AXIS_COLOR)
+    ];
+
+    for (events, color) in [(&body_events, DEBRIEF_BODY_COLOR), (&player_events, DEBRIEF_PLAYER_COLOR)] {
+        for pair in events.windows(2) {
+            let (start, end) = (to_diagram(pair[0].0), to_diagram(pair[1].0));
+            lines.push((start, end, color));
+
+            // A tick across the worldline wherever its clock passes a whole interval
+            if (pair[0].1 / DEBRIEF_TICK_INTERVAL).floor() != (pair[1].1 / DEBRIEF_TICK_INTERVAL).floor() {
+                let across = (end - start).perp().normalize_or_zero() * DEBRIEF_TICK_SIZE;
+                lines.push((end - across, end + across, color));
+            }
+        }
+    }
+
+    meshes.set_untracked(&diagram_mesh.0, line_mesh(lines));
+    diagram_visibility.is_visible = true;
+    background_visibility.is_visible = true;
+
+    info!("Compared clocks with {}: the player aged {:.2}s and {} aged {:.2}s", name, player_aged, name, body_aged);
+}
+
+// In the bottom right corner of the window, under the text, wherever the camera is
+#[allow(clippy::type_complexity)]
+fn place_debrief_diagram(
+    windows: Res<Windows>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    mut query_diagram: Query<&mut Transform, (With<DebriefDiagram>, Without<DebriefDiagramBackground>, Without<Camera2d>)>,
+    mut query_background: Query<&mut Transform, (With<DebriefDiagramBackground>, Without<Camera2d>)>
+) {
+    let (Some(window), Ok((camera, projection))) = (windows.get_primary(), query_camera.get_single()) else { return };
+    let corner = Vec2::new(window.width(), -window.height()) / 2.0
+        + Vec2::new(-DEBRIEF_MARGIN - DEBRIEF_DIAGRAM_SIZE.x, DEBRIEF_MARGIN);
+
+    *query_diagram.single_mut() = overlay_transform(camera, projection, corner, DEBRIEF_RENDER_DEPTH + 0.1);
+    *query_background.single_mut() = overlay_transform(camera, projection, corner + DEBRIEF_DIAGRAM_SIZE / 2.0, DEBRIEF_RENDER_DEPTH);
+}
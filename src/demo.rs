@@ -0,0 +1,188 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::MaterialMesh2dBundle
+};
+use rand::prelude::*;
+use std::f32::consts::PI;
+
+use super::*;
+
+// The game's look: the player, the background dust and the camera. What's around them comes from
+// the level being played, see LevelPlugin
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(DustSeed(thread_rng().gen()))
+            .add_startup_system(setup)
+            .add_system(apply_settings)
+            .add_system(move_dusties.after(apply_settings))
+            .add_system(point_player);
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+// Scatters the dust, kept so that a replay looks like the run it was recorded from
+#[derive(Resource)]
+pub struct DustSeed(pub u64);
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
+    query_player: Query<Entity, With<Player>>
+) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Gives the player something to be drawn with, apply_settings sets its size and colour
+    commands.entity(query_player.single()).insert(MaterialMesh2dBundle {
+        mesh: meshes.add(ship_mesh()).into(),
+        material: materials.add(ColorMaterial::default()),
+        transform: Transform::from_translation(Vec3::new(0.0, 0.0, settings.render_depths.player)),
+        ..default()
+    });
+}
+
+// Runs on the first frame and whenever the settings are reloaded or the dust is reseeded. The
+// background, the player's look, the angle markers and the dust follow the settings live,
+// everything else is read when it is spawned
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_settings(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut clear_color: ResMut<ClearColor>,
+    settings: Res<Settings>,
+    dust_seed: Res<DustSeed>,
+    windows: Res<Windows>,
+    mut query_player: Query<(&mut Transform, &Handle<ColorMaterial>), With<Player>>,
+    query_respawned: Query<Entity, Or<(With<AngleMarker>, With<Dusty>)>>
+) {
+    if !settings.is_changed() && !dust_seed.is_changed() {
+        return;
+    }
+
+    clear_color.0 = rgb(settings.background_color);
+
+    let player = &settings.player;
+    if let Ok((mut transform, material_handle)) = query_player.get_single_mut() {
+        transform.scale = Vec3::new(player.size, player.size, 0.0);
+
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.color = rgb(player.color);
+        }
+    }
+
+    for entity in query_respawned.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    // spawns all of the angle markers
+    let angle_markers = &settings.angle_markers;
+    for i in 0..angle_markers.count {
+        let angle = (i as f32) * (2.0 * PI) / (angle_markers.count as f32);
+
+        commands.spawn((MaterialMesh2dBundle {
+            mesh: meshes.add(shape::Circle::default().into()).into(),
+            material: materials.add(ColorMaterial::from(rgb(angle_markers.color))),
+            transform: Transform::from_translation((angle_markers.orbit_radius * Vec2::from_angle(angle)).extend(1.0))
+                .with_scale(Vec3::new(angle_markers.size, angle_markers.size, 0.0)),
+            ..default()
+        }, AngleMarker));
+    }
+
+    let Some(window) = windows.get_primary() else { return };
+    let width = window.width();
+    let height = window.height();
+    let dusties = &settings.dusties;
+    let mut rng = StdRng::seed_from_u64(dust_seed.0);
+
+    // spawns all of the dusties
+    for _ in 0..dusties.count {
+        let dist_x = width / 2.0 * rng.gen_range(-1.0..1.0);
+        let dist_y = height / 2.0 * rng.gen_range(-1.0..1.0);
+        let size = rng.gen_range(dusties.min_size..=dusties.max_size.max(dusties.min_size));
+
+        commands.spawn((MaterialMesh2dBundle {
+            mesh: meshes.add(shape::Circle::default().into()).into(),
+            material: materials.add(ColorMaterial::from(rgb(dusties.color))),
+            transform: Transform::from_translation(Vec3::new(dist_x, dist_y, 0.0))
+                .with_scale(Vec3::new(size, size, 0.0)),
+            ..default()
+        }, Dusty));
+    }
+}
+
+// Turns the ship to its heading. Seen from its own frame the screen is its rest frame, so it's
+// drawn as it points there, otherwise its nose is boosted into the frame being viewed from
+fn point_player(
+    rendered_player: Res<RenderedPlayer>,
+    view_frame: Res<ViewFrame>,
+    mut query_player: Query<&mut Transform, With<Player>>
+) {
+    let Ok(mut transform) = query_player.get_single_mut() else { return };
+    let PlayerState { velocity, heading, .. } = rendered_player.0;
+
+    let angle = if view_frame.body.is_none() {
+        heading
+    } else {
+        let world = heading_direction(heading, &Velocity(velocity));
+        let rotor = velocity_to_rotor(&Velocity(view_frame.velocity));
+
+        let mut nose = R120::new(0.0, 1);
+        nose[2] = world.x;
+        nose[3] = world.y;
+        nose = rotor * (nose * rotor.Reverse());
+
+        nose[3].atan2(nose[2])
+    };
+
+    transform.rotation = Quat::from_rotation_z(angle);
+}
+
+// Scrolls by how far the drawn player moved since the last frame, wrapping around whatever the
+// camera is looking at. Stops working if the ship can move a view width/height in a frame
+fn move_dusties(
+    rendered_player: Res<RenderedPlayer>,
+    mut last_position: Local<Option<Vec2>>,
+    mut dusties_transforms: Query<&mut Transform, (With<Dusty>, Without<Camera2d>)>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    windows: Res<Windows>
+) {
+    let position = rendered_player.0.position;
+    let moved = position - last_position.unwrap_or(position);
+    *last_position = Some(position);
+
+    let Some(window) = windows.get_primary() else { return };
+    let Ok((camera, projection)) = query_camera.get_single() else { return };
+    let size = Vec2::new(window.width(), window.height()) * projection.scale;
+    let corner = camera.translation.truncate() - size / 2.0;
+
+    for mut transform in dusties_transforms.iter_mut() {
+        let dusty = transform.translation.truncate() - moved - corner;
+
+        transform.translation.x = corner.x + dusty.x.rem_euclid(size.x);
+        transform.translation.y = corner.y + dusty.y.rem_euclid(size.y);
+    }
+}
+
+// A dart one unit long pointing along +x, centred on the origin like shape::Circle
+fn ship_mesh() -> Mesh {
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![
+        [0.5, 0.0, 0.0],
+        [-0.35, 0.3, 0.0],
+        [-0.2, 0.0, 0.0],
+        [-0.35, -0.3, 0.0]
+    ]);
+    mesh.set_indices(Some(Indices::U32(vec![0, 1, 2, 0, 2, 3])));
+
+    mesh
+}
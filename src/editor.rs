@@ -0,0 +1,371 @@
+use bevy::{asset::FileAssetIo, prelude::*, render::mesh::PrimitiveTopology, sprite::{MaterialMesh2dBundle, Mesh2dHandle}, transform::TransformSystem};
+use ron::ser::PrettyConfig;
+
+use super::*;
+
+// Controls, while the editor is open:
+//   Tab          open/close the editor, not while the level select is open as they share keys
+//   Left click   place a waypoint under the cursor, time_step after the last one
+//   Backspace    remove the last waypoint
+//   Up/Down      change the time step
+//   P            toggle periodic
+//   I            toggle Catmull-Rom interpolation
+//   Return       export to EDITOR_EXPORT_PATH in the assets folder
+
+const EDITOR_EXPORT_PATH: &str = "paths/editor.path.ron";
+const EDITOR_DEFAULT_TIME_STEP: f32 = 1.0;
+const EDITOR_TIME_STEP_CHANGE: f32 = 0.25;
+const EDITOR_PREVIEW_SAMPLES: usize = 16; // per segment
+const EDITOR_WAYPOINT_MARKER_SIZE: f32 = 6.0;
+
+const EDITOR_PATH_COLOR: Color = Color::rgb(120.0 / 256.0, 230.0 / 256.0, 130.0 / 256.0 );
+const EDITOR_SUPERLUMINAL_COLOR: Color = Color::rgb(255.0 / 256.0, 60.0 / 256.0, 60.0 / 256.0 );
+const EDITOR_LIGHT_CONE_COLOR: Color = Color::rgb(255.0 / 256.0, 220.0 / 256.0, 120.0 / 256.0 );
+const EDITOR_DIAGRAM_COLOR: Color = Color::rgba(10.0 / 256.0, 10.0 / 256.0, 25.0 / 256.0, 0.85);
+
+// Spacetime diagram of x against t, in the bottom right corner of the window
+const EDITOR_DIAGRAM_SIZE: Vec2 = Vec2::new(320.0, 220.0);
+const EDITOR_DIAGRAM_MARGIN: f32 = 20.0;
+
+const EDITOR_PREVIEW_RENDER_DEPTH: f32 = 8.0;
+const EDITOR_DIAGRAM_RENDER_DEPTH: f32 = 9.0;
+
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Editor>()
+            .add_startup_system(setup_editor)
+            .add_system(toggle_editor)
+            .add_system(edit_waypoints.after(toggle_editor))
+            .add_system(draw_editor.after(edit_waypoints))
+            // Once the camera has moved, so it doesn't trail a frame behind
+            .add_system_to_stage(CoreStage::PostUpdate, place_editor_diagram
+                .after(CameraSystem::Move)
+                .before(TransformSystem::TransformPropagate));
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct Editor {
+    pub active: bool,
+    waypoints: Vec<(Vec2, f32)>,
+    time_step: f32,
+    periodic: bool,
+    interpolation: Interpolation,
+    // The segments last warned about, so the warnings aren't repeated for every edit
+    superluminal: Vec<usize>
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Editor {
+            active: false,
+            waypoints: Vec::new(),
+            time_step: EDITOR_DEFAULT_TIME_STEP,
+            periodic: false,
+            interpolation: Interpolation::Linear,
+            superluminal: Vec::new()
+        }
+    }
+}
+
+impl Editor {
+    // None until there's a waypoint. Superluminal paths are kept so they can be shown in red
+    fn path(&self) -> Option<Path> {
+        Path::new(self.waypoints.clone(), self.periodic, self.interpolation, SuperluminalPolicy::Warn).ok()
+    }
+
+    fn to_asset(&self) -> PathAsset {
+        PathAsset {
+            periodic: self.periodic,
+            interpolation: self.interpolation,
+            before_start: PathEnd::Rest,
+            after_end: PathEnd::Rest,
+            superluminal: SuperluminalPolicy::Warn,
+            color: (EDITOR_PATH_COLOR.r(), EDITOR_PATH_COLOR.g(), EDITOR_PATH_COLOR.b()),
+            size: 10.0,
+            samples: self.waypoints.iter().map(|&(position, t)| PathSample { x: position.x, y: position.y, t }).collect()
+        }
+    }
+
+    // Into the same assets folder the game loads from, wherever it's run from
+    fn export(&self) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+        let path = FileAssetIo::get_base_path().join("assets").join(EDITOR_EXPORT_PATH);
+        let text = ron::ser::to_string_pretty(&self.to_asset(), PrettyConfig::default())?;
+        std::fs::write(&path, text)?;
+
+        Ok(path)
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct EditorPreview;
+
+#[derive(Component)]
+struct EditorDiagram;
+
+#[derive(Component)]
+struct EditorDiagramBackground;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup_editor(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>
+) {
+    commands.spawn((MaterialMesh2dBundle {
+        mesh: meshes.add(line_mesh(Vec::new())).into(),
+        material: materials.add(ColorMaterial::from(Color::WHITE)),
+        visibility: Visibility::INVISIBLE,
+        ..default()
+    }, EditorPreview));
+
+    commands.spawn((MaterialMesh2dBundle {
+        mesh: meshes.add(shape::Quad::new(EDITOR_DIAGRAM_SIZE).into()).into(),
+        material: materials.add(ColorMaterial::from(EDITOR_DIAGRAM_COLOR)),
+        visibility: Visibility::INVISIBLE,
+        ..default()
+    }, EditorDiagramBackground));
+
+    commands.spawn((MaterialMesh2dBundle {
+        mesh: meshes.add(line_mesh(Vec::new())).into(),
+        material: materials.add(ColorMaterial::from(Color::WHITE)),
+        visibility: Visibility::INVISIBLE,
+        ..default()
+    }, EditorDiagram));
+}
+
+fn toggle_editor(
+    keyboard_input: Res<Input<KeyCode>>,
+    level_select: Option<Res<LevelSelect>>,
+    mut editor: ResMut<Editor>
+) {
+    if keyboard_input.just_pressed(KeyCode::Tab) && !level_select.is_some_and(|level_select| level_select.open) {
+        editor.active = !editor.active;
+    }
+}
+
+fn edit_waypoints(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    rendered_player: Res<RenderedPlayer>,
+    windows: Res<Windows>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    mut editor: ResMut<Editor>
+) {
+    if !editor.active {
+        return;
+    }
+
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let window = windows.get_primary().expect("No primary window during edit_waypoints");
+        let (camera, projection) = query_camera.single();
+
+        if let Some(cursor) = cursor_in_view(window, camera, projection) {
+            let position = rendered_player.0.position + cursor;
+            let time = editor.waypoints.last().map_or(0.0, |&(_, time)| time + editor.time_step);
+
+            editor.waypoints.push((position, time));
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        editor.waypoints.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        editor.time_step += EDITOR_TIME_STEP_CHANGE;
+        info!("Editor time step: {:.2}s", editor.time_step);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        editor.time_step = (editor.time_step - EDITOR_TIME_STEP_CHANGE).max(EDITOR_TIME_STEP_CHANGE);
+        info!("Editor time step: {:.2}s", editor.time_step);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::P) {
+        editor.periodic = !editor.periodic;
+        info!("Editor path periodic: {}", editor.periodic);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::I) {
+        editor.interpolation = match editor.interpolation {
+            Interpolation::Linear => Interpolation::CatmullRom,
+            Interpolation::CatmullRom => Interpolation::Linear
+        };
+        info!("Editor path interpolation: {:?}", editor.interpolation);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        match editor.export() {
+            Ok(path) => info!("Exported path with {} waypoints to {:?}", editor.waypoints.len(), path),
+            Err(err) => error!("Couldn't export path to {}: {}", EDITOR_EXPORT_PATH, err)
+        }
+    }
+
+    if editor.is_changed() {
+        let segments = editor.path().map(|path| path.superluminal_segments()).unwrap_or_default();
+        let superluminal: Vec<usize> = segments.iter().map(|segment| segment.index).collect();
+
+        if superluminal != editor.superluminal {
+            for segment in segments.iter() {
+                warn!("Editor path isn't timelike, {}", segment);
+            }
+
+            if segments.is_empty() {
+                info!("Editor path is timelike again");
+            }
+
+            editor.superluminal = superluminal;
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_editor(
+    editor: Res<Editor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    rendered_player: Res<RenderedPlayer>,
+    mut query_preview: Query<(&mut Transform, &mut Visibility, &Mesh2dHandle), (With<EditorPreview>, Without<EditorDiagram>, Without<EditorDiagramBackground>)>,
+    mut query_diagram: Query<(&mut Visibility, &Mesh2dHandle), (With<EditorDiagram>, Without<EditorDiagramBackground>)>,
+    mut query_background: Query<&mut Visibility, With<EditorDiagramBackground>>
+) {
+    let (mut preview_transform, mut preview_visibility, preview_mesh) = query_preview.single_mut();
+    let (mut diagram_visibility, diagram_mesh) = query_diagram.single_mut();
+    let mut background_visibility = query_background.single_mut();
+
+    preview_visibility.is_visible = editor.active;
+    diagram_visibility.is_visible = editor.active;
+    background_visibility.is_visible = editor.active;
+
+    if !editor.active {
+        return;
+    }
+
+    // The preview is drawn in world space around the player, without reorienting it
+    let player_position = rendered_player.0.position;
+    preview_transform.translation = (-player_position).extend(EDITOR_PREVIEW_RENDER_DEPTH);
+
+    if !editor.is_changed() {
+        return;
+    }
+
+    let Some(path) = editor.path() else {
+        meshes.set_untracked(&preview_mesh.0, line_mesh(Vec::new()));
+        meshes.set_untracked(&diagram_mesh.0, line_mesh(Vec::new()));
+        return;
+    };
+
+    let superluminal: Vec<usize> = path.superluminal_segments().iter().map(|segment| segment.index).collect();
+    let segment_color = |index: usize| if superluminal.contains(&index) { EDITOR_SUPERLUMINAL_COLOR } else { EDITOR_PATH_COLOR };
+
+    // Each segment of the path as (position, time) points
+    let segments: Vec<(Vec<(Vec2, f32)>, Color)> = editor.waypoints.windows(2).enumerate().map(|(index, pair)| {
+        let points = (0..=EDITOR_PREVIEW_SAMPLES).map(|i| {
+            let time = pair[0].1 + (pair[1].1 - pair[0].1) * i as f32 / EDITOR_PREVIEW_SAMPLES as f32;
+            (path.position_at(time as f64), time)
+        }).collect();
+
+        (points, segment_color(index))
+    }).collect();
+
+    // Preview in world space, with a cross on each waypoint
+    let mut preview_lines = Vec::new();
+
+    for (points, color) in segments.iter() {
+        for pair in points.windows(2) {
+            preview_lines.push((pair[0].0, pair[1].0, *color));
+        }
+    }
+
+    // Periodic paths jump straight back to their first waypoint
+    if let (true, Some(&(first, _)), Some(&(last, _))) = (editor.periodic, editor.waypoints.first(), editor.waypoints.last()) {
+        preview_lines.push((last, first, segment_color(editor.waypoints.len() - 1)));
+    }
+
+    for &(position, _) in editor.waypoints.iter() {
+        preview_lines.push((position - Vec2::X * EDITOR_WAYPOINT_MARKER_SIZE, position + Vec2::X * EDITOR_WAYPOINT_MARKER_SIZE, EDITOR_PATH_COLOR));
+        preview_lines.push((position - Vec2::Y * EDITOR_WAYPOINT_MARKER_SIZE, position + Vec2::Y * EDITOR_WAYPOINT_MARKER_SIZE, EDITOR_PATH_COLOR));
+    }
+
+    meshes.set_untracked(&preview_mesh.0, line_mesh(preview_lines));
+
+    // Spacetime diagram of x against t, scaled to fit the waypoints
+    let min = editor.waypoints.iter().fold(Vec2::splat(f32::MAX), |min, &(position, time)| min.min(Vec2::new(position.x, time)));
+    let max = editor.waypoints.iter().fold(Vec2::splat(f32::MIN), |max, &(position, time)| max.max(Vec2::new(position.x, time)));
+    let size = (max - min).max(Vec2::new(100.0, editor.time_step));
+    let min = (min + max) / 2.0 - size * 0.55;
+    let max = min + size * 1.1;
+
+    let to_diagram = |x: f32, time: f32| (Vec2::new(x, time) - min) / (max - min) * EDITOR_DIAGRAM_SIZE;
+    let mut diagram_lines = Vec::new();
+
+    // Future light cone of each waypoint, clipped to the diagram
+    for &(position, time) in editor.waypoints.iter() {
+        for direction in [-1.0, 1.0] {
+            let edge = if direction > 0.0 { max.x } else { min.x };
+            let end_time = (time + (edge - position.x) * direction / SPEED_OF_LIGHT).min(max.y);
+
+            diagram_lines.push((
+                to_diagram(position.x, time),
+                to_diagram(position.x + direction * (end_time - time) * SPEED_OF_LIGHT, end_time),
+                EDITOR_LIGHT_CONE_COLOR
+            ));
+        }
+    }
+
+    for (points, color) in segments.iter() {
+        for pair in points.windows(2) {
+            diagram_lines.push((to_diagram(pair[0].0.x, pair[0].1), to_diagram(pair[1].0.x, pair[1].1), *color));
+        }
+    }
+
+    meshes.set_untracked(&diagram_mesh.0, line_mesh(diagram_lines));
+}
+
+// In the bottom right corner of the window, wherever the camera is
+#[allow(clippy::type_complexity)]
+fn place_editor_diagram(
+    editor: Res<Editor>,
+    windows: Res<Windows>,
+    query_camera: Query<(&Transform, &OrthographicProjection), With<Camera2d>>,
+    mut query_diagram: Query<&mut Transform, (With<EditorDiagram>, Without<EditorDiagramBackground>, Without<Camera2d>)>,
+    mut query_background: Query<&mut Transform, (With<EditorDiagramBackground>, Without<Camera2d>)>
+) {
+    if !editor.active {
+        return;
+    }
+
+    let (Some(window), Ok((camera, projection))) = (windows.get_primary(), query_camera.get_single()) else { return };
+    let corner = Vec2::new(window.width(), -window.height()) / 2.0
+        + Vec2::new(-EDITOR_DIAGRAM_MARGIN - EDITOR_DIAGRAM_SIZE.x, EDITOR_DIAGRAM_MARGIN);
+
+    *query_diagram.single_mut() = overlay_transform(camera, projection, corner, EDITOR_DIAGRAM_RENDER_DEPTH + 0.1);
+    *query_background.single_mut() = overlay_transform(camera, projection, corner + EDITOR_DIAGRAM_SIZE / 2.0, EDITOR_DIAGRAM_RENDER_DEPTH);
+}
+
+// Builds a mesh out of coloured lines. A degenerate line is added to empty meshes since they can't be drawn
+pub fn line_mesh(mut lines: Vec<(Vec2, Vec2, Color)>) -> Mesh {
+    if lines.is_empty() {
+        lines.push((Vec2::ZERO, Vec2::ZERO, Color::NONE));
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        lines.iter().flat_map(|(start, end, _)| [[start.x, start.y, 0.0], [end.x, end.y, 0.0]]).collect::<Vec<_>>()
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_COLOR,
+        lines.iter().flat_map(|(_, _, color)| [color.as_linear_rgba_f32(), color.as_linear_rgba_f32()]).collect::<Vec<_>>()
+    );
+
+    mesh
+}
@@ -0,0 +1,166 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::Deserialize;
+
+use super::*;
+
+// Runs the physics without a window, pressing keys from a script, e.g.
+//
+//     cargo run -- --headless assets/scripts/out_and_back.ron --telemetry run.csv
+//
+// Every update is exactly one physics step, so a script always plays out the same way however fast
+// the machine is. Scripts are RON files like
+//
+//     (
+//         duration: 20.0,
+//         inputs: [
+//             (time: 0.0, press: [D, T], aim: Some(1.57)),
+//             (time: 5.0, release: [D, T], press: [Space]),
+//         ],
+//     )
+//
+// where times are the player's proper time in seconds, and the run ends after `duration` of it.
+// The keys are the ones the default bindings use, and `aim` points the ship like the mouse or aim
+// stick would, in radians anticlockwise from +x. The script is played on every physics step and
+// read through the bindings there, so it also plays out the same with a fixed timestep
+
+pub struct HeadlessPlugin {
+    pub script: InputScript
+}
+
+impl HeadlessPlugin {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Couldn't read input script {:?}: {}", path, err))?;
+        let mut script: InputScript = ron::from_str(&text)
+            .map_err(|err| format!("Couldn't parse input script {:?}: {}", path, err))?;
+        script.inputs.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Ok(HeadlessPlugin { script })
+    }
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(self.script.clone())
+            .init_resource::<Input<KeyCode>>()
+            .add_system_set_to_stage(PhysicsStage::Step, SystemSet::new()
+                .with_run_criteria(PhysicsSteps)
+                .with_system(play_input_script.after(BindingsSystem::ReadActions))
+                .with_system(read_actions.after(play_input_script).before(BindingsSystem::ReadInput)))
+            .add_system_to_stage(CoreStage::Last, finish_input_script);
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource, Deserialize, Debug, Clone)]
+pub struct InputScript {
+    pub duration: f32,
+    pub inputs: Vec<ScriptedInput>,
+    // How many inputs have been played
+    #[serde(skip)]
+    next: usize
+}
+
+// Keys pressed and released at a proper time, in seconds, and where to point the ship then
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScriptedInput {
+    pub time: f32,
+    #[serde(default)]
+    pub press: Vec<ScriptedKey>,
+    #[serde(default)]
+    pub release: Vec<ScriptedKey>,
+    #[serde(default)]
+    pub aim: Option<f32>
+}
+
+// The keys the default bindings fly the ship with, see src/bindings.rs
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptedKey {
+    // Thrust along the axes
+    W,
+    A,
+    S,
+    D,
+    // Brake
+    Space,
+    // Forward and reverse along the ship's heading
+    T,
+    G,
+    // Turn left and right
+    Q,
+    E,
+    // Fire
+    F
+}
+
+impl From<ScriptedKey> for KeyCode {
+    fn from(key: ScriptedKey) -> Self {
+        match key {
+            ScriptedKey::W => KeyCode::W,
+            ScriptedKey::A => KeyCode::A,
+            ScriptedKey::S => KeyCode::S,
+            ScriptedKey::D => KeyCode::D,
+            ScriptedKey::Space => KeyCode::Space,
+            ScriptedKey::T => KeyCode::T,
+            ScriptedKey::G => KeyCode::G,
+            ScriptedKey::Q => KeyCode::Q,
+            ScriptedKey::E => KeyCode::E,
+            ScriptedKey::F => KeyCode::F
+        }
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// Stands in for the InputPlugin, which isn't part of MinimalPlugins. Runs before every physics step,
+// with the Actions read again after it, and aims through the Actions like the mouse does
+fn play_input_script(
+    mut script: ResMut<InputScript>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut actions: ResMut<Actions>,
+    local_time: Res<LocalTime>
+) {
+    keyboard_input.clear();
+
+    while let Some(input) = script.inputs.get(script.next) {
+        if input.time > local_time.0 {
+            break;
+        }
+
+        for key in input.release.iter() {
+            keyboard_input.release(KeyCode::from(*key));
+        }
+
+        for key in input.press.iter() {
+            keyboard_input.press(KeyCode::from(*key));
+        }
+
+        if let Some(aim) = input.aim {
+            actions.aim = Some(aim);
+        }
+
+        script.next += 1;
+    }
+}
+
+fn finish_input_script(
+    script: Res<InputScript>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    query_player: Query<(&Position, &Velocity), With<Player>>,
+    mut app_exit_events: EventWriter<AppExit>
+) {
+    if local_time.0 < script.duration {
+        return;
+    }
+
+    let (Position(position), Velocity(velocity)) = query_player.single();
+    info!(
+        "Script finished at global time {:.4}, local time {:.4}, position {}, velocity {}",
+        global_time.0, local_time.0, position, velocity
+    );
+
+    app_exit_events.send(AppExit);
+}
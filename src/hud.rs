@@ -0,0 +1,139 @@
+use bevy::{diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin}, prelude::*};
+use std::fmt::Write;
+
+use super::*;
+
+// Controls:
+//   H    show/hide the HUD
+//   F3   show/hide the debug overlay under it
+
+const HUD_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const HUD_FONT_SIZE: f32 = 16.0;
+const HUD_MARGIN: f32 = 10.0;
+
+const HUD_COLOR: Color = Color::rgb(220.0 / 256.0, 220.0 / 256.0, 235.0 / 256.0 );
+const HUD_DEBUG_COLOR: Color = Color::rgb(150.0 / 256.0, 150.0 / 256.0, 170.0 / 256.0 );
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Hud>()
+            .add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_startup_system(setup_hud)
+            .add_system(toggle_hud)
+            .add_system(update_hud.after(toggle_hud));
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct Hud {
+    pub visible: bool,
+    pub debug: bool
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Hud { visible: true, debug: false }
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct HudText;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(HUD_FONT);
+
+    // The first section holds the player's clocks and motion, the second the debug overlay
+    commands.spawn((TextBundle::from_sections([
+        TextSection::new("", TextStyle { font: font.clone(), font_size: HUD_FONT_SIZE, color: HUD_COLOR }),
+        TextSection::new("", TextStyle { font, font_size: HUD_FONT_SIZE, color: HUD_DEBUG_COLOR })
+    ]).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            top: Val::Px(HUD_MARGIN),
+            left: Val::Px(HUD_MARGIN),
+            ..default()
+        },
+        ..default()
+    }), HudText));
+}
+
+fn toggle_hud(keyboard_input: Res<Input<KeyCode>>, mut hud: ResMut<Hud>) {
+    if keyboard_input.just_pressed(KeyCode::H) {
+        hud.visible = !hud.visible;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        hud.debug = !hud.debug;
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_hud(
+    hud: Res<Hud>,
+    diagnostics: Res<Diagnostics>,
+    rendered_player: Res<RenderedPlayer>,
+    physics_clock: Res<PhysicsClock>,
+    query_bodies: Query<&ComputedVisibility, Or<(With<Path>, With<AnalyticPath>)>>,
+    mut query_text: Query<(&mut Text, &mut Visibility), With<HudText>>
+) {
+    let (mut text, mut visibility) = query_text.single_mut();
+
+    if visibility.is_visible != hud.visible {
+        visibility.is_visible = hud.visible;
+    }
+
+    if !hud.visible {
+        return;
+    }
+
+    let PlayerState { position, velocity, global_time, local_time, heading } = rendered_player.0;
+    let beta = velocity.length() / SPEED_OF_LIGHT;
+    let gamma = 1.0 / (1.0 - beta.powi(2)).sqrt();
+    let course = if velocity == Vec2::ZERO {
+        "-".to_string()
+    } else {
+        format!("{:.1}°", velocity.y.atan2(velocity.x).to_degrees())
+    };
+
+    let readout = &mut text.sections[0].value;
+    readout.clear();
+    let _ = writeln!(readout, "Coordinate time {:>10.3} s", global_time);
+    let _ = writeln!(readout, "Proper time     {:>10.3} s", local_time);
+    let _ = writeln!(readout, "Speed           {:>10.4} c", beta);
+    let _ = writeln!(readout, "Lorentz factor  {:>10.4}", gamma);
+    let _ = writeln!(readout, "Rapidity        {:>10.4}", beta.atanh());
+    let _ = writeln!(readout, "Heading         {:>9.1}°", heading.to_degrees());
+    let _ = writeln!(readout, "Course          {:>10}", course);
+
+    if physics_clock.paused {
+        let _ = writeln!(readout, "Time speed      {:>10}", "paused");
+    } else if physics_clock.speed != 1.0 {
+        let _ = writeln!(readout, "Time speed      {:>9}x", physics_clock.speed);
+    }
+
+    let overlay = &mut text.sections[1].value;
+    overlay.clear();
+
+    if hud.debug {
+        let fps = diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+            .unwrap_or(0.0);
+        let visible_bodies = query_bodies.iter().filter(|visibility| visibility.is_visible()).count();
+
+        let _ = writeln!(overlay);
+        let _ = writeln!(overlay, "FPS             {:>10.1}", fps);
+        let _ = writeln!(overlay, "Position        {:>10.1} {:>10.1}", position.x, position.y);
+        let _ = writeln!(overlay, "Velocity        {:>10.1} {:>10.1}", velocity.x, velocity.y);
+        let _ = writeln!(overlay, "Bodies in view  {:>10} / {}", visible_bodies, query_bodies.iter().count());
+    }
+}
@@ -0,0 +1,603 @@
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::TypeUuid,
+    sprite::MaterialMesh2dBundle
+};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+use super::*;
+
+// Levels are RON files in assets/levels, listed in the level select in file name order, e.g.
+//
+//     (
+//         name: "Sandbox",
+//         briefing: "Fly around and watch the clocks",
+//         bounds: Some((left: -5000.0, right: 5000.0, lower: -5000.0, upper: 5000.0)),
+//         player: (position: (0.0, -500.0), velocity: (0.0, 0.0)),
+//         bodies: [
+//             (name: "second hand", color: (1.0, 0.0, 0.0), size: 10.0, clock: Some(60.0), doppler_shaded: true,
+//                 motion: orbit(center: (0.0, 0.0), radius: 100.0, period: 60.0)),
+//             (motion: file("paths/orbit.path.ron")),
+//             (color: (0.5, 1.0, 0.8), motion: line(position: (-3000.0, 900.0), velocity: (150.0, -40.0))),
+//         ],
+//         points: [(position: (0.0, 0.0), size: 20.0), (name: "home", position: (0.0, -500.0), clock: Some(60.0))],
+//         objectives: [(description: "Reach the gate before your clock reads 60s", goal: arrive(position: (3000.0, 0.0), before: Some(60.0)))],
+//     )
+//
+// Everything but the name can be left out. Bounds default to the ones in the settings, and only
+// bodies moving along a Path (orbit, samples or file) can be Doppler shaded. See
+// src/objectives.rs for the goals, which refer to bodies and points by name
+//
+// Controls:
+//   L             open/close the level select, not while the editor is open as they share keys
+//   Up/Down       choose a level
+//   Return        play it, or restart it
+//   1-9           play the level with that number
+
+const LEVEL_SELECT_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const LEVEL_SELECT_FONT_SIZE: f32 = 18.0;
+const LEVEL_SELECT_COLOR: Color = Color::rgb(220.0 / 256.0, 220.0 / 256.0, 235.0 / 256.0 );
+const LEVEL_SELECT_BACKGROUND: Color = Color::rgba(10.0 / 256.0, 10.0 / 256.0, 25.0 / 256.0, 0.85);
+
+const LEVELS_FOLDER: &str = "levels";
+const DEFAULT_ORBIT_SAMPLES: usize = 12;
+
+pub struct LevelPlugin {
+    // A level to start in rather than opening the level select, relative to the assets folder
+    pub start: Option<String>
+}
+
+impl LevelPlugin {
+    // Reads --level <file>
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        LevelPlugin { start: args.find(|arg| arg == "--level").and_then(|_| args.next()) }
+    }
+}
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        let start = self.start.clone();
+
+        app
+            .add_asset::<LevelAsset>()
+            .init_asset_loader::<LevelAssetLoader>()
+            .insert_resource(LevelSelect { open: start.is_none(), selected: 0, pending: None })
+            .add_startup_system(move |mut commands: Commands, asset_server: Res<AssetServer>, mut level_select: ResMut<LevelSelect>| {
+                commands.insert_resource(Levels::load(&asset_server));
+
+                if let Some(start) = &start {
+                    level_select.pending = Some(asset_server.load(start.as_str()));
+                }
+            })
+            .add_startup_system(setup_level_select)
+            .add_system(choose_level)
+            .add_system(load_level.after(choose_level))
+            .add_system(draw_level_select.after(choose_level));
+    }
+}
+
+// ----------------------------------<< Assets >>----------------------------------
+
+#[derive(Serialize, Deserialize, TypeUuid, Debug, Clone)]
+#[uuid = "9d4f2b61-3c8e-4a07-b5e2-6a1d0f7c93e4"]
+pub struct LevelAsset {
+    pub name: String,
+    #[serde(default)]
+    pub briefing: String,
+    #[serde(default)]
+    pub bounds: Option<BoundsSettings>,
+    #[serde(default)]
+    pub player: LevelPlayer,
+    #[serde(default)]
+    pub bodies: Vec<LevelBody>,
+    #[serde(default)]
+    pub points: Vec<LevelPoint>,
+    // Draws the hour ticks from the clock_dial settings around the origin
+    #[serde(default)]
+    pub clock_dial: bool,
+    #[serde(default)]
+    pub objectives: Vec<Objective>,
+    // Gives the objectives one at a time, see src/objectives.rs
+    #[serde(default)]
+    pub guided: bool,
+    // The body whose clock is compared with the player's once every objective is done, see
+    // src/debrief.rs
+    #[serde(default)]
+    pub compare_with: Option<String>
+}
+
+// Where the player starts, at coordinate and proper time 0
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LevelPlayer {
+    #[serde(default)]
+    pub position: (f32, f32),
+    #[serde(default)]
+    pub velocity: (f32, f32),
+    // Radians anticlockwise from +x, in the player's rest frame
+    #[serde(default)]
+    pub heading: f32
+}
+
+// Kept on the body's entity, so the bodies still around can be saved as they were spawned
+#[derive(Component, Serialize, Deserialize, Debug, Clone)]
+pub struct LevelBody {
+    // For objectives to refer to it by
+    #[serde(default)]
+    pub name: String,
+    // File paths bring their own, which is also the one Doppler shading tints from
+    #[serde(default = "default_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default = "default_size")]
+    pub size: f32,
+    // Seconds of proper time for the hand of its clock to go around, see ClockFace
+    #[serde(default)]
+    pub clock: Option<f32>,
+    #[serde(default)]
+    pub doppler_shaded: bool,
+    pub motion: Motion
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelPoint {
+    // For objectives to refer to it by
+    #[serde(default)]
+    pub name: String,
+    pub position: (f32, f32),
+    #[serde(default = "default_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default = "default_size")]
+    pub size: f32,
+    // Seconds of coordinate time for the hand of its clock to go around, see ClockFace
+    #[serde(default)]
+    pub clock: Option<f32>
+}
+
+// How a body moves, times are coordinate times in seconds
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Motion {
+    // A path file in assets, see src/path_asset.rs
+    File(String),
+    // A Path written out in the level
+    Samples {
+        #[serde(default)]
+        periodic: bool,
+        #[serde(default)]
+        interpolation: Interpolation,
+        #[serde(default)]
+        before_start: PathEnd,
+        #[serde(default)]
+        after_end: PathEnd,
+        samples: Vec<PathSample>
+    },
+    // A sampled circle, going anticlockwise from `phase` radians
+    Orbit {
+        center: (f32, f32),
+        radius: f32,
+        period: f32,
+        #[serde(default)]
+        phase: f32,
+        #[serde(default = "default_orbit_samples")]
+        samples: usize
+    },
+    // The analytic trajectories, see src/trajectory.rs
+    Line {
+        position: (f32, f32),
+        velocity: (f32, f32),
+        #[serde(default)]
+        time: f64
+    },
+    Circle {
+        center: (f32, f32),
+        radius: f32,
+        period: f32,
+        #[serde(default)]
+        phase: f32
+    },
+    UniformAcceleration {
+        position: (f32, f32),
+        #[serde(default)]
+        speed: f32,
+        direction: (f32, f32),
+        acceleration: f32,
+        #[serde(default)]
+        time: f64
+    }
+}
+
+fn default_color() -> (f32, f32, f32) { (1.0, 1.0, 1.0) }
+fn default_size() -> f32 { 10.0 }
+fn default_orbit_samples() -> usize { DEFAULT_ORBIT_SAMPLES }
+
+fn vec2((x, y): (f32, f32)) -> Vec2 {
+    Vec2::new(x, y)
+}
+
+impl Motion {
+    // None for bodies that aren't sampled
+    fn path(&self) -> Option<Result<Path, PathError>> {
+        match self {
+            Motion::Samples { periodic, interpolation, before_start, after_end, samples } => Some(Path::new(
+                samples.iter().map(|sample| (Vec2::new(sample.x, sample.y), sample.t)).collect(),
+                *periodic,
+                *interpolation,
+                SuperluminalPolicy::Warn
+            ).map(|path| path.with_ends(*before_start, *after_end))),
+            Motion::Orbit { center, radius, period, phase, samples } => Some(Ok(Path::from_paremetric_equation(
+                *phase,
+                *phase + TAU,
+                *period,
+                (*samples).max(3),
+                Interpolation::CatmullRom,
+                |angle| vec2(*center) + *radius * Vec2::from_angle(angle)
+            ))),
+            _ => None
+        }
+    }
+
+    fn analytic_path(&self) -> Option<AnalyticPath> {
+        match self {
+            Motion::Line { position, velocity, time } => Some(AnalyticPath::new(Line {
+                position: vec2(*position),
+                velocity: vec2(*velocity),
+                time: *time
+            })),
+            Motion::Circle { center, radius, period, phase } => Some(AnalyticPath::new(Circle {
+                center: vec2(*center),
+                radius: *radius,
+                period: *period,
+                phase: *phase
+            })),
+            Motion::UniformAcceleration { position, speed, direction, acceleration, time } => Some(AnalyticPath::new(UniformAcceleration {
+                position: vec2(*position),
+                speed: *speed,
+                direction: vec2(*direction),
+                acceleration: *acceleration,
+                time: *time
+            })),
+            _ => None
+        }
+    }
+}
+
+// ----------------------------------<< Loaders >>----------------------------------
+
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+impl AssetLoader for LevelAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let level: LevelAsset = ron::de::from_bytes(bytes)?;
+
+            for (i, body) in level.bodies.iter().enumerate() {
+                match body.motion.path() {
+                    Some(Err(err)) => return Err(Error::msg(format!("Body {} in level {:?} can't be made: {}", i, load_context.path(), err))),
+                    Some(Ok(path)) => if let Some(segment) = path.superluminal_segments().first() {
+                        warn!("Body {} in level {:?} isn't timelike, {}", i, load_context.path(), segment);
+                    },
+                    None => {}
+                }
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+// Every level in assets/levels, in file name order
+#[derive(Resource)]
+pub struct Levels(pub Vec<Handle<LevelAsset>>);
+
+impl Levels {
+    fn load(asset_server: &AssetServer) -> Self {
+        let mut handles = match asset_server.load_folder(LEVELS_FOLDER) {
+            Ok(handles) => handles.into_iter().map(|handle| handle.typed::<LevelAsset>()).collect(),
+            Err(err) => {
+                error!("Couldn't load the levels in assets/{}: {}", LEVELS_FOLDER, err);
+                Vec::new()
+            }
+        };
+
+        handles.sort_by_key(|handle| asset_server.get_handle_path(handle).map(|path| path.path().to_path_buf()));
+        Levels(handles)
+    }
+}
+
+#[derive(Resource)]
+pub struct LevelSelect {
+    pub open: bool,
+    pub selected: usize,
+    // Waiting to be loaded before it's played
+    pub pending: Option<Handle<LevelAsset>>
+}
+
+// The level being played, as it was when it started
+#[derive(Resource)]
+pub struct CurrentLevel {
+    // The default handle when it came from a saved game rather than a file
+    pub handle: Handle<LevelAsset>,
+    pub asset: LevelAsset
+}
+
+impl CurrentLevel {
+    // Relative to the assets folder
+    pub fn file(&self, asset_server: &AssetServer) -> Option<String> {
+        asset_server.get_handle_path(&self.handle).map(|path| path.path().to_string_lossy().into_owned())
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+// Everything spawned by a level, despawned when another one is loaded
+#[derive(Component)]
+pub struct LevelEntity;
+
+#[derive(Component)]
+struct LevelSelectMenu;
+
+#[derive(Component)]
+struct LevelSelectText;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup_level_select(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Percent(25.0),
+                left: Val::Percent(25.0),
+                ..default()
+            },
+            padding: UiRect::all(Val::Px(16.0)),
+            ..default()
+        },
+        background_color: LEVEL_SELECT_BACKGROUND.into(),
+        ..default()
+    }, LevelSelectMenu)).with_children(|parent| {
+        parent.spawn((TextBundle::from_section("", TextStyle {
+            font: asset_server.load(LEVEL_SELECT_FONT),
+            font_size: LEVEL_SELECT_FONT_SIZE,
+            color: LEVEL_SELECT_COLOR
+        }), LevelSelectText));
+    });
+}
+
+fn choose_level(
+    keyboard_input: Res<Input<KeyCode>>,
+    levels: Option<Res<Levels>>,
+    current_level: Option<Res<CurrentLevel>>,
+    editor: Option<Res<Editor>>,
+    mut level_select: ResMut<LevelSelect>
+) {
+    let Some(levels) = levels else { return };
+    let editing = editor.is_some_and(|editor| editor.active);
+
+    if keyboard_input.just_pressed(KeyCode::L) && !editing {
+        if let Some(current_level) = &current_level {
+            level_select.open = !level_select.open;
+
+            // So that Return plays it again
+            if let Some(i) = levels.0.iter().position(|handle| *handle == current_level.handle) {
+                level_select.selected = i;
+            }
+        }
+    }
+
+    if !level_select.open || levels.0.is_empty() {
+        return;
+    }
+
+    const NUMBER_KEYS: [KeyCode; 9] = [
+        KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+        KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9
+    ];
+
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        level_select.selected = (level_select.selected + levels.0.len() - 1) % levels.0.len();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        level_select.selected = (level_select.selected + 1) % levels.0.len();
+    }
+
+    let number = NUMBER_KEYS.iter().position(|key| keyboard_input.just_pressed(*key)).filter(|i| *i < levels.0.len());
+
+    if let Some(number) = number {
+        level_select.selected = number;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) || number.is_some() {
+        level_select.pending = Some(levels.0[level_select.selected].clone());
+        level_select.open = false;
+    }
+}
+
+// Plays the pending level once it has loaded, and restarts the current one when its file changes
+fn load_level(
+    mut asset_events: EventReader<AssetEvent<LevelAsset>>,
+    mut level_select: ResMut<LevelSelect>,
+    mut level_spawner: LevelSpawner,
+    level_assets: Res<Assets<LevelAsset>>,
+    current_level: Option<Res<CurrentLevel>>
+) {
+    if let Some(current_level) = &current_level {
+        let modified = asset_events.iter().any(|event| matches!(event, AssetEvent::Modified { handle } if *handle == current_level.handle));
+
+        if modified && level_select.pending.is_none() {
+            info!("Restarting {} since its file changed", current_level.asset.name);
+            level_select.pending = Some(current_level.handle.clone());
+        }
+    }
+
+    let Some(handle) = level_select.pending.clone() else { return };
+    let Some(level) = level_assets.get(&handle) else { return };
+    level_select.pending = None;
+
+    level_spawner.spawn(handle, level);
+}
+
+// Everything needed to swap the level being played for another, see LevelSpawner::spawn
+#[derive(SystemParam)]
+pub struct LevelSpawner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<ColorMaterial>>,
+    pub player_reset: ResMut<'w, PlayerReset>,
+    pub asset_server: Res<'w, AssetServer>,
+    pub settings: Res<'w, Settings>,
+    pub query_level_entities: Query<'w, 's, Entity, With<LevelEntity>>
+}
+
+impl<'w, 's> LevelSpawner<'w, 's> {
+    // Despawns the current level and starts this one from the beginning
+    pub fn spawn(&mut self, handle: Handle<LevelAsset>, level: &LevelAsset) {
+        for entity in self.query_level_entities.iter() {
+            self.commands.entity(entity).despawn_recursive();
+        }
+
+        self.player_reset.state = Some(PlayerState {
+            position: vec2(level.player.position),
+            velocity: vec2(level.player.velocity),
+            global_time: 0.0,
+            local_time: 0.0,
+            heading: level.player.heading
+        });
+        self.player_reset.history.clear();
+
+        let mut rng = thread_rng();
+        let depths = &self.settings.render_depths;
+
+        for body in level.bodies.iter() {
+            // Levels are checked when they load, but saves aren't
+            let path = match body.motion.path().transpose() {
+                Ok(path) => path,
+                Err(err) => {
+                    error!("Couldn't spawn body \"{}\": {}", body.name, err);
+                    continue;
+                }
+            };
+
+            let mut entity = self.commands.spawn((MaterialMesh2dBundle {
+                mesh: self.meshes.add(shape::Circle::default().into()).into(),
+                material: self.materials.add(ColorMaterial::from(rgb(body.color))),
+                transform: Transform::from_translation(Vec3::new(f32::MAX, f32::MAX, depths.path + rng.gen_range(-0.001..0.001)))
+                    .with_scale(Vec3::new(body.size, body.size, 0.0)),
+                ..default()
+            }, body.clone(), LevelEntity));
+
+            match (&body.motion, path, body.motion.analytic_path()) {
+                (Motion::File(file), _, _) => { entity.insert(self.asset_server.load::<PathAsset, _>(file.as_str())); },
+                (_, Some(path), _) => { entity.insert(path); },
+                (_, _, Some(analytic_path)) => { entity.insert(analytic_path); },
+                _ => {}
+            }
+
+            if let Some(period) = body.clock {
+                entity.insert(ClockFace { period });
+            }
+
+            if !body.name.is_empty() {
+                entity.insert(Name::new(body.name.clone()));
+            }
+
+            if body.doppler_shaded {
+                entity.insert(DopplerShaded(rgb(body.color)));
+            }
+        }
+
+        let dial = &self.settings.clock_dial;
+        let dial_ticks = (0..12).filter(|_| level.clock_dial).map(|i| LevelPoint {
+            position: (dial.radius * Vec2::from_angle(i as f32 * TAU / 12.0)).into(),
+            color: dial.color,
+            size: dial.tick_size,
+            clock: None,
+            name: String::new()
+        });
+
+        for point in level.points.iter().cloned().chain(dial_ticks) {
+            let mut entity = self.commands.spawn((MaterialMesh2dBundle {
+                mesh: self.meshes.add(shape::Circle::default().into()).into(),
+                material: self.materials.add(ColorMaterial::from(rgb(point.color))),
+                transform: Transform::from_translation(vec2(point.position).extend(depths.point + rng.gen_range(-0.001..0.001)))
+                    .with_scale(Vec3::new(point.size, point.size, 0.0)),
+                ..default()
+            }, Point, Position(vec2(point.position)), LevelEntity));
+
+            if let Some(period) = point.clock {
+                entity.insert(ClockFace { period });
+            }
+
+            if !point.name.is_empty() {
+                entity.insert(Name::new(point.name.clone()));
+            }
+        }
+
+        info!("Playing level {}", level.name);
+        self.commands.insert_resource(CurrentLevel { handle, asset: level.clone() });
+        self.commands.insert_resource(Objectives::new(level.objectives.clone(), level.guided));
+    }
+}
+
+fn draw_level_select(
+    levels: Option<Res<Levels>>,
+    level_select: Res<LevelSelect>,
+    level_assets: Res<Assets<LevelAsset>>,
+    mut query_menu: Query<&mut Visibility, With<LevelSelectMenu>>,
+    mut query_text: Query<&mut Text, With<LevelSelectText>>
+) {
+    let Some(levels) = levels else { return };
+    let mut visibility = query_menu.single_mut();
+    let mut text = query_text.single_mut();
+
+    if visibility.is_visible != level_select.open {
+        visibility.is_visible = level_select.open;
+    }
+
+    if !level_select.open {
+        return;
+    }
+
+    let mut menu = String::from("Choose a level (Up/Down, Return)\n\n");
+
+    for (i, handle) in levels.0.iter().enumerate() {
+        let cursor = if i == level_select.selected { ">" } else { " " };
+
+        match level_assets.get(handle) {
+            Some(level) => menu += &format!("{} {}. {}\n", cursor, i + 1, level.name),
+            None => menu += &format!("{} {}. (loading)\n", cursor, i + 1)
+        }
+    }
+
+    if let Some(level) = levels.0.get(level_select.selected).and_then(|handle| level_assets.get(handle)) {
+        if !level.briefing.is_empty() {
+            menu += &format!("\n{}\n", level.briefing);
+        }
+
+        for objective in level.objectives.iter() {
+            menu += &format!("  - {}\n", objective.description);
+        }
+    }
+
+    if levels.0.is_empty() {
+        menu += &format!("There aren't any levels in assets/{}\n", LEVELS_FOLDER);
+    }
+
+    text.sections[0].value = menu;
+}
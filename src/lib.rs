@@ -0,0 +1,168 @@
+use bevy::prelude::*;
+
+pub mod r120;
+pub use r120::R120;
+
+mod reorient;
+pub use reorient::{ReorientPlugin, ViewFrame};
+use reorient::{heading_direction, velocity_to_rotor};
+
+mod path;
+pub use path::{Interpolation, Path, PathEnd, PathError, Sighting, SuperluminalPolicy, SuperluminalSegment};
+use path::find_light_cone_root;
+
+mod path_asset;
+pub use path_asset::{PathAsset, PathAssetLoader, PathAssetPlugin, PathSample};
+
+mod trajectory;
+pub use trajectory::{AnalyticPath, Circle, Line, Trajectory, UniformAcceleration};
+
+mod physics;
+pub use physics::{
+    PhysicsClock, PhysicsStage, PhysicsSteps, PhysicsSystem, PlayerInput, PlayerReset, PlayerState, PreviousStep,
+    RenderedPlayer, SpacetimePhysicsPlugin, Worldline
+};
+
+mod clock;
+pub use clock::{BodyMotion, ClockFace, ClockPlugin};
+
+mod editor;
+pub use editor::{Editor, EditorPlugin};
+use editor::line_mesh;
+
+mod hud;
+pub use hud::{Hud, HudPlugin};
+
+mod telemetry;
+pub use telemetry::{Telemetry, TelemetryFormat, TelemetryPlugin, TelemetrySample};
+
+mod headless;
+pub use headless::{HeadlessPlugin, InputScript, ScriptedInput, ScriptedKey};
+
+mod settings;
+pub use settings::{
+    AngleMarkerSettings, BoundsSettings, ClockDialSettings, DopplerSettings, DustSettings, PlayerSettings,
+    RenderDepthSettings, Settings, SettingsLoader, SettingsPlugin, PLAYER_MAX_SPEED
+};
+use settings::rgb;
+
+mod bindings;
+pub use bindings::{Actions, Binding, Bindings, BindingsLoader, BindingsPlugin, BindingsSystem, Stick};
+use bindings::read_actions;
+
+mod level;
+pub use level::{
+    CurrentLevel, LevelAsset, LevelAssetLoader, LevelBody, LevelEntity, LevelPlayer, LevelPlugin, LevelPoint,
+    LevelSelect, LevelSpawner, Levels, Motion
+};
+
+mod objectives;
+pub use objectives::{Goal, Objective, ObjectiveState, Objectives, ObjectivesPlugin};
+
+mod debrief;
+pub use debrief::DebriefPlugin;
+
+mod save;
+pub use save::{PendingLoad, SaveFile, SaveGame, SavePlugin, SavedPlayerState, SAVE_VERSION};
+
+mod replay;
+pub use replay::{
+    PendingReplay, RecordedInput, Recorder, Recording, Replay, ReplayFile, ReplayPlugin, ReplaySystem, SettingsChange,
+    REPLAY_VERSION
+};
+
+mod rewind;
+pub use rewind::{Rewind, RewindPlugin};
+
+mod time_control;
+pub use time_control::TimeControlPlugin;
+
+mod camera;
+pub use camera::{CameraPlugin, CameraSystem, CameraView, Follow};
+use camera::{cursor_in_view, overlay_transform};
+
+mod demo;
+pub use demo::{DemoPlugin, DustSeed};
+
+// color palette:
+// https://htmlcolorcodes.com/
+
+// ----------------------------------<< Constants >>----------------------------------
+
+pub const TIMESTEP: f32 = 1.0 / 60.0;
+
+pub const SPEED_OF_LIGHT: f32 = 4000.0;
+
+// ----------------------------------<< Plugins >>----------------------------------
+
+// Simulates the player and draws the world the way they see it: the physics step, everything
+// reoriented onto the player's past light cone, Path assets, clock faces and Doppler shading.
+// Needs DefaultPlugins, the game adds its scene, editor and HUD on top of this
+pub struct SpacetimePlugin;
+
+impl Plugin for SpacetimePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugin(SpacetimePhysicsPlugin { fixed_timestep: true })
+            .add_plugin(ReorientPlugin)
+            .add_plugin(PathAssetPlugin)
+            .add_plugin(ClockPlugin)
+            .add_system(check_paths_are_timelike);
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct GlobalTime(pub f32);
+
+#[derive(Resource)]
+pub struct LocalTime(pub f32);
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+pub struct Player;
+
+#[derive(Component)]
+pub struct AngleMarker;
+
+#[derive(Component)]
+pub struct Point;
+
+#[derive(Component)]
+pub struct Dusty;
+
+#[derive(Component)]
+pub struct Velocity(pub Vec2);
+
+#[derive(Component)]
+pub struct Position(pub Vec2);
+
+// Which way the ship points in its own rest frame, in radians anticlockwise from +x
+#[derive(Component)]
+pub struct Heading(pub f32);
+
+// Tints a Path by how blue or red shifted the player sees it, holding the colour it has at rest
+#[derive(Component)]
+pub struct DopplerShaded(pub Color);
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// Anything faster than light breaks the light cone solve in reorient_paths
+fn check_paths_are_timelike(query_paths: Query<(Entity, &Path), Added<Path>>) {
+    for (entity, path) in query_paths.iter() {
+        let (start, end) = path.time_range();
+        debug!(
+            "Path on {:?} has a top speed of {:.3}c and ages {:.3}s over its {:.3}s",
+            entity,
+            path.max_speed() / SPEED_OF_LIGHT,
+            path.proper_time_at(end as f64) - path.proper_time_at(start as f64),
+            end - start
+        );
+
+        for segment in path.superluminal_segments() {
+            warn!("Path on {:?} isn't timelike, {}", entity, segment);
+        }
+    }
+}
@@ -0,0 +1,437 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use super::*;
+
+// A level's goals, checked every physics step against the step the player just took. Times in
+// goals are proper times in seconds, the player's own clock unless it says the body's, e.g.
+//
+//     objectives: [
+//         (description: "Reach the far beacon", goal: arrive(position: (8000.0, 0.0), before: Some(30.0))),
+//         (description: "Meet the courier at noon", goal: meet(body: "courier", time: 12.0)),
+//         (description: "See the beacon strike 40", goal: signal(body: "beacon", time: 40.0)),
+//         (description: "Go home", hint: "Your twin is waiting", goal: reach(body: "twin")),
+//     ]
+//
+// Bodies are found by their name in the level. A guided level gives its objectives one at a time,
+// in order, with the hint of the current one shown under it. Hints can name an action from the
+// bindings in braces, e.g. "Hold {thrust_right} to speed up", which shows whatever it's bound to
+
+const OBJECTIVE_DEFAULT_RADIUS: f32 = 50.0;
+const OBJECTIVE_DEFAULT_TOLERANCE: f32 = 1.0;
+
+const OBJECTIVES_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const OBJECTIVES_FONT_SIZE: f32 = 16.0;
+const OBJECTIVES_MARGIN: f32 = 10.0;
+
+const OBJECTIVES_COLOR: Color = Color::rgb(220.0 / 256.0, 220.0 / 256.0, 235.0 / 256.0 );
+
+pub struct ObjectivesPlugin;
+
+impl Plugin for ObjectivesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Objectives>()
+            .add_system_set_to_stage(PhysicsStage::Step, SystemSet::new()
+                .with_run_criteria(PhysicsSteps)
+                .with_system(check_objectives.after(PhysicsSystem::Move)))
+            .add_startup_system(setup_objectives)
+            .add_system(draw_objectives);
+    }
+}
+
+// ----------------------------------<< Types >>----------------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Objective {
+    pub description: String,
+    #[serde(default)]
+    pub hint: String,
+    pub goal: Goal
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Goal {
+    // Get within `radius` of `position`, before your clock reads `before`
+    Arrive {
+        position: (f32, f32),
+        #[serde(default = "default_radius")]
+        radius: f32,
+        #[serde(default)]
+        before: Option<f32>
+    },
+    // Be within `radius` of the body while its clock reads `time`, give or take `tolerance`
+    Meet {
+        body: String,
+        time: f32,
+        #[serde(default = "default_radius")]
+        radius: f32,
+        #[serde(default = "default_tolerance")]
+        tolerance: f32
+    },
+    // Get within `radius` of the body, whatever its clock reads
+    Reach {
+        body: String,
+        #[serde(default = "default_radius")]
+        radius: f32
+    },
+    // See the body's clock read `time`, i.e. have the light it gave off then reach you, before
+    // your clock reads `before`
+    Signal {
+        body: String,
+        time: f32,
+        #[serde(default)]
+        before: Option<f32>
+    }
+}
+
+fn default_radius() -> f32 { OBJECTIVE_DEFAULT_RADIUS }
+fn default_tolerance() -> f32 { OBJECTIVE_DEFAULT_TOLERANCE }
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectiveState {
+    Pending,
+    // With the player's proper time when it was done
+    Complete(f32),
+    Failed
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+// The current level's objectives and how they're going, replaced when a level is loaded
+#[derive(Resource, Default)]
+pub struct Objectives {
+    pub objectives: Vec<(Objective, ObjectiveState)>,
+    pub guided: bool
+}
+
+impl Objectives {
+    pub fn new(objectives: Vec<Objective>, guided: bool) -> Self {
+        Objectives {
+            objectives: objectives.into_iter().map(|objective| (objective, ObjectiveState::Pending)).collect(),
+            guided
+        }
+    }
+
+    pub fn all_complete(&self) -> bool {
+        !self.objectives.is_empty() && self.objectives.iter().all(|(_, state)| matches!(state, ObjectiveState::Complete(_)))
+    }
+
+    pub fn any_failed(&self) -> bool {
+        self.objectives.iter().any(|(_, state)| *state == ObjectiveState::Failed)
+    }
+
+    // The one being worked on in a guided level. Failed ones are passed over so the rest can still
+    // be played, though the level can't be completed any more
+    fn current(&self) -> Option<usize> {
+        self.objectives.iter().position(|(_, state)| *state == ObjectiveState::Pending)
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct ObjectivesText;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// Runs in the physics step after the player has moved. Every goal is checked along the step the
+// player just took, from PreviousStep to where they are now, so nothing is missed between frames
+#[allow(clippy::type_complexity)]
+fn check_objectives(
+    mut objectives: ResMut<Objectives>,
+    previous_step: Res<PreviousStep>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    query_player: Query<&Position, With<Player>>,
+    query_bodies: Query<(&Name, Option<&Path>, Option<&AnalyticPath>, Option<&Position>)>
+) {
+    if objectives.objectives.is_empty() {
+        return;
+    }
+
+    let step_end = PlayerState {
+        position: query_player.single().0,
+        global_time: global_time.0,
+        local_time: local_time.0,
+        ..previous_step.0
+    };
+
+    let find_body = |name: &str| query_bodies.iter()
+        .find(|(body_name, ..)| body_name.as_str() == name)
+        .and_then(|(_, path, analytic_path, position)| BodyMotion::new(path, analytic_path, position));
+    let was_done = objectives.all_complete() || objectives.any_failed();
+    let current = objectives.current();
+    let guided = objectives.guided;
+
+    for (i, (objective, state)) in objectives.objectives.iter_mut().enumerate() {
+        if *state != ObjectiveState::Pending || (guided && Some(i) != current) {
+            continue;
+        }
+
+        // Bodies that aren't there (yet) leave it pending
+        let Some(checked) = check_goal(&objective.goal, &previous_step.0, &step_end, find_body) else { continue };
+        *state = checked;
+
+        match state {
+            ObjectiveState::Complete(at) => info!("Objective complete at {:.2}s: {}", at, objective.description),
+            ObjectiveState::Failed => info!("Objective failed: {}", objective.description),
+            ObjectiveState::Pending => {}
+        }
+    }
+
+    if !was_done && objectives.all_complete() {
+        info!("Every objective is complete, at proper time {:.2}s and coordinate time {:.2}s", step_end.local_time, step_end.global_time);
+    }
+}
+
+// How a goal stands after the step from `start` to `end`, or None when its body can't be found.
+// Within a step the player and the body are taken to move in straight lines at steady rates
+fn check_goal<'a>(goal: &Goal, start: &PlayerState, end: &PlayerState, find_body: impl Fn(&str) -> Option<BodyMotion<'a>>) -> Option<ObjectiveState> {
+    let local_time = end.local_time;
+    let too_late = |before: &Option<f32>| before.is_some_and(|before| local_time > before);
+
+    // Where the body is from the player and what its clock reads, at the start and end of the step
+    let relative = |motion: &BodyMotion| {
+        let (start_position, start_time) = motion.event(start.global_time as f64);
+        let (end_position, end_time) = motion.event(end.global_time as f64);
+
+        ((start_position - start.position, start_time as f32), (end_position - end.position, end_time as f32))
+    };
+
+    Some(match goal {
+        Goal::Arrive { position, radius, before } => {
+            if too_late(before) {
+                ObjectiveState::Failed
+            } else if distance_to_segment(Vec2::new(position.0, position.1), start.position, end.position) <= *radius {
+                ObjectiveState::Complete(local_time)
+            } else {
+                ObjectiveState::Pending
+            }
+        },
+        Goal::Meet { body, time, radius, tolerance } => {
+            let ((start_offset, start_time), (end_offset, end_time)) = relative(&find_body(body)?);
+
+            // The part of the step the body's clock is within tolerance of the time for
+            let (lower, upper) = if end_time == start_time {
+                if (start_time - time).abs() <= *tolerance { (0.0, 1.0) } else { (1.0, 0.0) }
+            } else {
+                let lower = (time - tolerance - start_time) / (end_time - start_time);
+                let upper = (time + tolerance - start_time) / (end_time - start_time);
+                (lower.min(upper).max(0.0), lower.max(upper).min(1.0))
+            };
+
+            let met = lower <= upper
+                && distance_to_segment(Vec2::ZERO, start_offset.lerp(end_offset, lower), start_offset.lerp(end_offset, upper)) <= *radius;
+
+            if met {
+                ObjectiveState::Complete(local_time)
+            } else if end_time > time + tolerance {
+                ObjectiveState::Failed
+            } else {
+                ObjectiveState::Pending
+            }
+        },
+        Goal::Reach { body, radius } => {
+            let ((start_offset, _), (end_offset, _)) = relative(&find_body(body)?);
+
+            if distance_to_segment(Vec2::ZERO, start_offset, end_offset) <= *radius {
+                ObjectiveState::Complete(local_time)
+            } else {
+                ObjectiveState::Pending
+            }
+        },
+        // What the player sees of a clock only goes forward, so the end of the step covers all of it
+        Goal::Signal { body, time, before } => {
+            let seen = find_body(body)?.seen_proper_time(&Position(end.position), end.global_time as f64);
+
+            if seen.is_some_and(|seen| seen as f32 >= *time) {
+                ObjectiveState::Complete(local_time)
+            } else if too_late(before) {
+                ObjectiveState::Failed
+            } else {
+                ObjectiveState::Pending
+            }
+        }
+    })
+}
+
+fn distance_to_segment(point: Vec2, start: Vec2, end: Vec2) -> f32 {
+    let along = end - start;
+    let t = if along == Vec2::ZERO { 0.0 } else { ((point - start).dot(along) / along.length_squared()).clamp(0.0, 1.0) };
+
+    point.distance(start + t * along)
+}
+
+// Swaps each {action} in a hint for what it's bound to, leaving anything else in braces alone
+fn fill_in_bindings(hint: &str, bindings: Option<&Bindings>) -> String {
+    let mut filled = String::new();
+    let mut rest = hint;
+
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else { break };
+        let name = &rest[open + 1..close];
+
+        filled.push_str(&rest[..open]);
+
+        match bindings.and_then(|bindings| bindings.action(name)) {
+            Some([]) => filled.push_str(&format!("{} (unbound)", name.replace('_', " "))),
+            Some(bound) => filled.push_str(&bound.iter().map(|binding| binding.to_string()).collect::<Vec<_>>().join(" or ")),
+            None => filled.push_str(&rest[open..=close])
+        }
+
+        rest = &rest[close + 1..];
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+fn setup_objectives(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((TextBundle::from_section("", TextStyle {
+        font: asset_server.load(OBJECTIVES_FONT),
+        font_size: OBJECTIVES_FONT_SIZE,
+        color: OBJECTIVES_COLOR
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            top: Val::Px(OBJECTIVES_MARGIN),
+            right: Val::Px(OBJECTIVES_MARGIN),
+            ..default()
+        },
+        ..default()
+    }), ObjectivesText));
+}
+
+fn draw_objectives(
+    objectives: Res<Objectives>,
+    bindings: Option<Res<Bindings>>,
+    mut query_text: Query<&mut Text, With<ObjectivesText>>
+) {
+    if !objectives.is_changed() && !bindings.as_ref().is_some_and(|bindings| bindings.is_changed()) {
+        return;
+    }
+
+    let text = &mut query_text.single_mut().sections[0].value;
+    text.clear();
+
+    let current = objectives.current();
+
+    for (i, (objective, state)) in objectives.objectives.iter().enumerate() {
+        if objectives.guided && current.is_some_and(|current| i > current) {
+            break;
+        }
+
+        let _ = match state {
+            ObjectiveState::Pending => writeln!(text, "[ ] {}", objective.description),
+            ObjectiveState::Complete(at) => writeln!(text, "[x] {} ({:.1}s)", objective.description, at),
+            ObjectiveState::Failed => writeln!(text, "[!] {}", objective.description)
+        };
+
+        if objectives.guided && Some(i) == current && !objective.hint.is_empty() {
+            let _ = writeln!(text, "    {}", fill_in_bindings(&objective.hint, bindings.as_deref()));
+        }
+    }
+
+    if objectives.all_complete() {
+        let _ = writeln!(text, "\nLevel complete! L for the level select");
+    } else if objectives.any_failed() {
+        let _ = writeln!(text, "\nLevel failed, Return in the level select (L) to try again");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Moving along the x axis, with the clocks keeping coordinate time
+    fn state(x: f32, global_time: f32) -> PlayerState {
+        PlayerState { position: Vec2::new(x, 0.0), global_time, local_time: global_time, ..default() }
+    }
+
+    // A body sitting still at `position`, whatever it's called
+    fn check(goal: Goal, start: PlayerState, end: PlayerState, position: Vec2) -> ObjectiveState {
+        check_goal(&goal, &start, &end, |_| Some(BodyMotion::AtRest(position))).unwrap()
+    }
+
+    fn arrive(before: Option<f32>) -> Goal {
+        Goal::Arrive { position: (0.0, 10.0), radius: 20.0, before }
+    }
+
+    fn meet(time: f32, tolerance: f32) -> Goal {
+        Goal::Meet { body: "beacon".to_string(), time, radius: 20.0, tolerance }
+    }
+
+    #[test]
+    fn arrive_counts_passing_through() {
+        assert_eq!(check(arrive(None), state(-100.0, 1.0), state(100.0, 1.1), Vec2::ZERO), ObjectiveState::Complete(1.1));
+        assert_eq!(check(arrive(None), state(-100.0, 1.0), state(-50.0, 1.1), Vec2::ZERO), ObjectiveState::Pending);
+        assert_eq!(check(arrive(Some(1.05)), state(-100.0, 1.0), state(100.0, 1.1), Vec2::ZERO), ObjectiveState::Failed);
+    }
+
+    #[test]
+    fn meet_counts_passing_through_at_the_time() {
+        assert_eq!(check(meet(12.0, 0.05), state(-100.0, 11.9), state(100.0, 12.1), Vec2::ZERO), ObjectiveState::Complete(12.1));
+        assert_eq!(check(meet(20.0, 1.0), state(-100.0, 11.9), state(100.0, 12.1), Vec2::ZERO), ObjectiveState::Pending);
+        assert_eq!(check(meet(5.0, 1.0), state(-100.0, 11.9), state(100.0, 12.1), Vec2::ZERO), ObjectiveState::Failed);
+    }
+
+    #[test]
+    fn meet_needs_to_be_close_while_the_clock_is_right() {
+        // The clock reads 11 while the player is still 80 away, and they only meet at 11.5
+        assert_eq!(check(meet(11.0, 0.1), state(-100.0, 11.0), state(100.0, 12.0), Vec2::ZERO), ObjectiveState::Failed);
+        assert_eq!(check(meet(11.5, 0.1), state(-100.0, 11.0), state(100.0, 12.0), Vec2::ZERO), ObjectiveState::Complete(12.0));
+    }
+
+    #[test]
+    fn reach_counts_passing_through() {
+        let reach = || Goal::Reach { body: "beacon".to_string(), radius: 20.0 };
+
+        assert_eq!(check(reach(), state(-100.0, 1.0), state(100.0, 1.1), Vec2::new(0.0, 10.0)), ObjectiveState::Complete(1.1));
+        assert_eq!(check(reach(), state(-100.0, 1.0), state(100.0, 1.1), Vec2::new(0.0, 30.0)), ObjectiveState::Pending);
+    }
+
+    #[test]
+    fn signal_waits_for_the_light() {
+        let signal = |time, before| Goal::Signal { body: "beacon".to_string(), time, before };
+
+        // A light second away, so at 5s the player sees it read 4s
+        let beacon = Vec2::new(SPEED_OF_LIGHT, 0.0);
+
+        assert_eq!(check(signal(3.5, None), state(0.0, 4.9), state(0.0, 5.0), beacon), ObjectiveState::Complete(5.0));
+        assert_eq!(check(signal(4.5, None), state(0.0, 4.9), state(0.0, 5.0), beacon), ObjectiveState::Pending);
+        assert_eq!(check(signal(4.5, Some(4.95)), state(0.0, 4.9), state(0.0, 5.0), beacon), ObjectiveState::Failed);
+    }
+
+    #[test]
+    fn missing_bodies_leave_goals_pending() {
+        let goal = Goal::Reach { body: "nobody".to_string(), radius: 20.0 };
+
+        assert_eq!(check_goal(&goal, &state(0.0, 0.0), &state(0.0, 0.1), |_| None), None);
+    }
+
+    #[test]
+    fn hints_name_the_bindings() {
+        let mut bindings = Bindings { thrust_right: vec![Binding::Key(KeyCode::D)], ..default() };
+        bindings.turn_left.clear();
+
+        assert_eq!(fill_in_bindings("Hold {thrust_right} to speed up", Some(&bindings)), "Hold D to speed up");
+        assert_eq!(fill_in_bindings("{turn_left}, {nothing} {", Some(&bindings)), "turn left (unbound), {nothing} {");
+        assert_eq!(fill_in_bindings("Hold {thrust_right}", None), "Hold {thrust_right}");
+    }
+
+    #[test]
+    fn guided_levels_carry_on_past_a_failure() {
+        let objective = |description: &str| Objective { description: description.to_string(), hint: String::new(), goal: arrive(None) };
+        let mut objectives = Objectives::new(vec![objective("first"), objective("second")], true);
+
+        assert_eq!(objectives.current(), Some(0));
+
+        objectives.objectives[0].1 = ObjectiveState::Failed;
+        assert_eq!(objectives.current(), Some(1));
+        assert!(objectives.any_failed() && !objectives.all_complete());
+    }
+}
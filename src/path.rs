@@ -0,0 +1,752 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::*;
+
+// Bisection alone converges well within this, the Newton steps usually only need a few
+const LIGHT_CONE_MAX_ITERATIONS: usize = 64;
+const LIGHT_CONE_TOLERANCE: f64 = 1e-4; // in units of ct
+
+// Path::clamp_to_light_speed slows segments down to this, a bit under light speed so the light
+// cone solve stays well conditioned
+const CLAMPED_PATH_SPEED: f32 = 0.99 * SPEED_OF_LIGHT;
+
+// Always has at least one sample, see Path::new
+#[derive(Component, Debug)]
+pub struct Path {
+    samples: Vec<(Vec2, f32)>,
+    periodic: bool,
+    interpolation: Interpolation,
+    // What a non-periodic path does outside of its first and last samples
+    before_start: PathEnd,
+    after_end: PathEnd,
+    // Proper time (in seconds) elapsed between the first sample and each sample, and over one
+    // whole period of a periodic path. Kept up to date by everything that changes the samples
+    proper_times: Vec<f64>,
+    period_proper_time: f64,
+    // The index found by the last light cone lookup, the next one is almost always the same or one after
+    last_index: AtomicUsize
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PathEnd {
+    // Sits still at the first/last sample
+    #[default]
+    Rest,
+    // Doesn't exist before the start event or after the end event
+    Despawn
+}
+
+// Where the player sees a path, see Path::past_light_cone_event
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sighting {
+    // The event on the past light cone, relative to the player and in units of ct
+    At(Vec2, f64),
+    // Light from the start event hasn't reached the player yet
+    BeforeStart,
+    // Light from the end event has already passed the player, so it's only seen again from an
+    // earlier event
+    AfterEnd
+}
+
+// What to do with a path that moves at or above the speed of light somewhere
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SuperluminalPolicy {
+    // Keep it as it is, check_paths_are_timelike logs the offending segments once it's spawned
+    #[default]
+    Warn,
+    // Slow the offending segments down, see Path::clamp_to_light_speed
+    Clamp,
+    // Fail to make it
+    Reject
+}
+
+// Why Path::new couldn't make a path
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathError {
+    NoSamples,
+    Superluminal(Vec<SuperluminalSegment>)
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathError::NoSamples => write!(f, "the path has no samples"),
+            PathError::Superluminal(segments) => write!(
+                f,
+                "the path isn't timelike: {}",
+                segments.iter().map(|segment| segment.to_string()).collect::<Vec<_>>().join(", ")
+            )
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+// How the worldline is drawn between samples
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    // Straight segments
+    #[default]
+    Linear,
+    // Cubic Hermite segments with Catmull-Rom tangents (the finite difference of the neighbouring
+    // samples), which passes through every sample with a continuous velocity
+    CatmullRom
+}
+
+// A piece of a Path that moves at or above the speed of light, see Path::superluminal_segments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuperluminalSegment {
+    // The segment goes from this sample to the next one, or wraps around to the first sample
+    pub index: usize,
+    pub speed: f32
+}
+
+impl fmt::Display for SuperluminalSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "segment {} moves at {:.1} ({:.3}c)", self.index, self.speed, self.speed / SPEED_OF_LIGHT)
+    }
+}
+
+// Where a path is at some coordinate time, see Path::locate
+enum Location {
+    // A single sample or the rest before/after a non-periodic path, with the proper time there
+    Resting(Vec2, f64),
+    // Part way along a segment, with the proper time at the start of the segment
+    Moving(Segment, f64, f64)
+}
+
+// One piece of the worldline between two samples, with positions relative to the player and times in ct
+struct Segment {
+    start: (Vec2, f64),
+    end: (Vec2, f64),
+    start_tangent: Vec2, // d(position)/d(ct)
+    end_tangent: Vec2,
+    interpolation: Interpolation
+}
+
+impl Path {
+    // Checks the samples against the superluminal policy once they're interpolated
+    pub fn new(samples: Vec<(Vec2, f32)>, periodic: bool, interpolation: Interpolation, superluminal: SuperluminalPolicy) -> Result<Self, PathError> {
+        if samples.is_empty() {
+            return Err(PathError::NoSamples);
+        }
+
+        let mut path = Path {
+            samples,
+            periodic,
+            interpolation,
+            before_start: PathEnd::Rest,
+            after_end: PathEnd::Rest,
+            proper_times: Vec::new(),
+            period_proper_time: 0.0,
+            last_index: AtomicUsize::new(0)
+        };
+
+        path.update_proper_times();
+
+        match superluminal {
+            SuperluminalPolicy::Warn => Ok(path),
+            SuperluminalPolicy::Clamp => Ok(path.clamp_to_light_speed()),
+            SuperluminalPolicy::Reject => match path.superluminal_segments() {
+                segments if segments.is_empty() => Ok(path),
+                segments => Err(PathError::Superluminal(segments))
+            }
+        }
+    }
+
+    // Always has a sample, so is never rejected for having none
+    pub fn from_paremetric_equation(min: f32, max: f32, total_time: f32, samples: usize, interpolation: Interpolation, eq: impl Fn(f32) -> Vec2) -> Self {
+        let mut path = Vec::new();
+
+        for i in 0..(samples + 1) {
+            path.push((
+                eq((i as f32) * (max - min) / (samples as f32) + min),
+                (i as f32) * total_time / (samples as f32)
+            ));
+        }
+
+        Path::new(path, true, interpolation, SuperluminalPolicy::Warn).expect("Path::from_paremetric_equation made no samples")
+    }
+
+    // Samples a trajectory between two times, e.g. to get a Path that can be composed or saved
+    pub fn from_trajectory(trajectory: &impl Trajectory, start_time: f32, end_time: f32, samples: usize, periodic: bool) -> Self {
+        Path::new(
+            (0..(samples + 1)).map(|i| {
+                let time = start_time + (i as f32) * (end_time - start_time) / (samples as f32);
+                (trajectory.position(time as f64), time)
+            }).collect(),
+            periodic,
+            Interpolation::Linear,
+            SuperluminalPolicy::Warn
+        ).expect("Path::from_trajectory made no samples")
+    }
+
+    pub fn with_ends(mut self, before_start: PathEnd, after_end: PathEnd) -> Self {
+        self.before_start = before_start;
+        self.after_end = after_end;
+        self
+    }
+
+    // Stretches out the segments that are too fast so that the path can be followed below the
+    // speed of light, delaying every sample after them
+    pub fn clamp_to_light_speed(mut self) -> Self {
+        // Catmull-Rom tangents depend on the neighbouring segments, so this can take a few passes
+        for _ in 0..4 {
+            let segments = self.superluminal_segments();
+            if segments.is_empty() {
+                break;
+            }
+
+            for SuperluminalSegment { index, speed } in segments {
+                if index + 1 >= self.samples.len() {
+                    continue; // The wrap around of a periodic path can't be stretched
+                }
+
+                let duration = self.samples[index + 1].1 - self.samples[index].1;
+                let stretched = if duration > 0.0 && speed.is_finite() {
+                    duration * speed / CLAMPED_PATH_SPEED
+                } else {
+                    (self.samples[index + 1].0 - self.samples[index].0).length() / CLAMPED_PATH_SPEED
+                };
+
+                for sample in self.samples[(index + 1)..].iter_mut() {
+                    sample.1 += stretched - duration;
+                }
+            }
+        }
+
+        self.update_proper_times();
+        self
+    }
+
+    // Every segment that moves at or above the speed of light. Spline segments are checked at
+    // a few points along them since they can overshoot between timelike samples
+    pub fn superluminal_segments(&self) -> Vec<SuperluminalSegment> {
+        (0..self.segment_count())
+            .map(|index| SuperluminalSegment { index, speed: self.segment_speed(index) })
+            .filter(|segment| segment.speed >= SPEED_OF_LIGHT)
+            .collect()
+    }
+
+    // The fastest coordinate speed anywhere along the path
+    pub fn max_speed(&self) -> f32 {
+        (0..self.segment_count()).map(|index| self.segment_speed(index)).fold(0.0, f32::max)
+    }
+
+    fn segment_count(&self) -> usize {
+        if self.periodic { self.samples.len() } else { self.samples.len() - 1 }
+    }
+
+    fn segment_speed(&self, index: usize) -> f32 {
+        let origin = Position(Vec2::ZERO);
+        let segment = if index + 1 == self.samples.len() {
+            self.segment((index, -(self.period() as f64)), (0, 0.0), &origin)
+        } else {
+            self.segment((index, 0.0), (index + 1, 0.0), &origin)
+        };
+
+        // Periodic paths normally end where they start, allowing for rounding errors in the samples
+        let duration = segment.end.1 - segment.start.1;
+        if duration <= 0.0 {
+            return if segment.start.0.distance(segment.end.0) < 1e-2 { 0.0 } else { f32::INFINITY };
+        }
+
+        // |d(position)/ds| / d(ct)/ds is the speed as a fraction of c
+        (0..=4)
+            .map(|i| segment.derivative(i as f64 / 4.0).length())
+            .fold(0.0, f32::max) / duration as f32 * SPEED_OF_LIGHT
+    }
+
+    // Shifts the path in time so that its first sample happens at start_time
+    pub fn starting_at(mut self, start_time: f32) -> Self {
+        let shift = start_time - self.samples[0].1;
+
+        for sample in self.samples.iter_mut() {
+            sample.1 += shift;
+        }
+
+        self
+    }
+
+    // Follows this path once and then next, moved in space and time so that it starts where and
+    // when this one ends. The result is never periodic and keeps this path's interpolation and start
+    pub fn then(mut self, next: Path) -> Self {
+        let (end_position, end_time) = *self.samples.last().expect("Path was empty in Path::then");
+        let (next_position, next_time) = next.samples[0];
+
+        self.samples.extend(next.samples.iter().skip(1).map(|(position, time)| (
+            *position - next_position + end_position,
+            *time - next_time + end_time
+        )));
+        self.periodic = false;
+        self.after_end = next.after_end;
+        self.update_proper_times();
+
+        self
+    }
+
+    // In ct, from the first sample to the last, which is where the next period's first sample is
+    pub fn period(&self) -> f32 {
+        let (start, end) = self.time_range();
+        (end - start) * SPEED_OF_LIGHT
+    }
+
+    // The coordinate times (in seconds) of the first and last samples
+    pub fn time_range(&self) -> (f32, f32) {
+        (self.samples[0].1, self.samples[self.samples.len() - 1].1)
+    }
+
+    // Where the path is at a coordinate time (in seconds). Non-periodic paths rest at their ends
+    pub fn position_at(&self, time: f64) -> Vec2 {
+        match self.locate(time) {
+            Location::Resting(position, _) => position,
+            Location::Moving(segment, s, _) => segment.position(s)
+        }
+    }
+
+    // The coordinate velocity (in pixels per second) at a coordinate time (in seconds)
+    pub fn velocity_at(&self, time: f64) -> Vec2 {
+        match self.locate(time) {
+            Location::Resting(..) => Vec2::ZERO,
+            Location::Moving(segment, s, _) => segment.velocity(s)
+        }
+    }
+
+    // The time dilation factor at a coordinate time (in seconds), infinite at or above light speed
+    pub fn gamma_at(&self, time: f64) -> f32 {
+        let beta_squared = self.velocity_at(time).length_squared() / SPEED_OF_LIGHT.powi(2);
+
+        if beta_squared < 1.0 { 1.0 / (1.0 - beta_squared).sqrt() } else { f32::INFINITY }
+    }
+
+    // The proper time (in seconds) a clock riding on the path has ticked through since the first
+    // sample, at a coordinate time (in seconds). Negative before the first sample
+    pub fn proper_time_at(&self, time: f64) -> f64 {
+        match self.locate(time) {
+            Location::Resting(_, proper_time) => proper_time,
+            Location::Moving(segment, s, proper_time) => proper_time + segment.proper_time(s)
+        }
+    }
+
+    // The segment the path is on at a coordinate time (in seconds) and how far along it it is
+    fn locate(&self, time: f64) -> Location {
+        let origin = Position(Vec2::ZERO);
+        let seconds = time;
+        let time = time * SPEED_OF_LIGHT as f64; // turn time units to ct
+        let len = self.samples.len();
+        let (first, last) = (self.samples[0], self.samples[len - 1]);
+
+        let start = first.1 as f64 * SPEED_OF_LIGHT as f64;
+        let periods = if self.periodic && self.period() > 0.0 { ((time - start) / self.period() as f64).floor() } else { 0.0 };
+        let offset = self.period() as f64 * periods;
+
+        // get the index of the first rest stop after the time
+        let i = self.samples.partition_point(|sample| sample.1 as f64 * SPEED_OF_LIGHT as f64 + offset <= time);
+
+        let (segment, proper_time) = match i {
+            _ if len == 1 => return Location::Resting(first.0, seconds - first.1 as f64),
+            0 if self.periodic => (
+                self.segment((len - 1, offset - self.period() as f64), (0, offset), &origin),
+                (periods - 1.0) * self.period_proper_time + self.proper_times[len - 1]
+            ),
+            0 => return Location::Resting(first.0, seconds - first.1 as f64),
+            _ if i == len => return Location::Resting(last.0, self.proper_times[len - 1] + seconds - last.1 as f64),
+            _ => (
+                self.segment((i - 1, offset), (i, offset), &origin),
+                periods * self.period_proper_time + self.proper_times[i - 1]
+            )
+        };
+
+        let duration = segment.end.1 - segment.start.1;
+        let s = if duration > 0.0 { ((time - segment.start.1) / duration).clamp(0.0, 1.0) } else { 1.0 };
+
+        Location::Moving(segment, s, proper_time)
+    }
+
+    // Integrates the proper time along every segment, see proper_times
+    fn update_proper_times(&mut self) {
+        let origin = Position(Vec2::ZERO);
+        let len = self.samples.len();
+        let mut elapsed = 0.0;
+
+        self.proper_times = Vec::with_capacity(len);
+        self.proper_times.push(0.0);
+
+        for i in 1..len {
+            elapsed += self.segment((i - 1, 0.0), (i, 0.0), &origin).proper_time(1.0);
+            self.proper_times.push(elapsed);
+        }
+
+        self.period_proper_time = if self.periodic && len > 1 {
+            elapsed + self.segment((len - 1, -(self.period() as f64)), (0, 0.0), &origin).proper_time(1.0)
+        } else {
+            elapsed
+        };
+    }
+
+    // Finds the event where the player's past light cone crosses the path, this is where the
+    // player sees it
+    pub fn past_light_cone_event(&self, player_position: &Position, global_time: f64) -> Sighting {
+        let global_time = global_time * SPEED_OF_LIGHT as f64; // turn time units to ct
+        let first = self.samples.first().expect("Path was empty in Path::past_light_cone_event");
+        let last = self.samples.last().expect("Path was empty in Path::past_light_cone_event");
+
+        let resting_at = |position: Vec2| Sighting::At(position - player_position.0, global_time - (position - player_position.0).length() as f64);
+
+        if self.samples.len() == 1 {
+            return resting_at(first.0);
+        }
+
+        let offset = self.light_cone_offset(player_position, global_time);
+        let i = self.first_outside_light_cone(player_position, global_time, offset);
+
+        // Only non-periodic paths can have finished or not started yet
+        if i == self.samples.len() {
+            return match self.after_end {
+                PathEnd::Rest => resting_at(last.0),
+                PathEnd::Despawn => Sighting::AfterEnd
+            };
+        }
+
+        if i == 0 && !self.periodic {
+            return match self.before_start {
+                PathEnd::Rest => resting_at(first.0),
+                PathEnd::Despawn => Sighting::BeforeStart
+            };
+        }
+
+        // Periodic paths wrap around from the end of the previous period to their first sample
+        let segment = if i == 0 {
+            self.segment((self.samples.len() - 1, offset - self.period() as f64), (0, offset), player_position)
+        } else {
+            self.segment((i - 1, offset), (i, offset), player_position)
+        };
+
+        segment.past_light_cone_event(global_time)
+    }
+
+    // How far (in ct) the samples of a periodic path are moved along for the period the light cone
+    // crosses, going by its last sample, 0 for other paths
+    fn light_cone_offset(&self, player_position: &Position, global_time: f64) -> f64 {
+        if !self.periodic {
+            return 0.0;
+        }
+
+        let (first, last) = (self.samples[0], self.samples[self.samples.len() - 1]);
+        let seen = global_time - (last.0 - player_position.0).length() as f64 - first.1 as f64 * SPEED_OF_LIGHT as f64;
+        self.period() as f64 * (seen / self.period() as f64).floor()
+    }
+
+    // Gets the index of the first rest stop that is above the light cone, or samples.len() if there
+    // isn't one. The gap shrinks along a timelike path, so the cached index is checked first and
+    // otherwise the samples are binary searched
+    fn first_outside_light_cone(&self, player_position: &Position, global_time: f64, offset: f64) -> usize {
+        let len = self.samples.len();
+        let is_inside = |i: usize| i < len && self.light_cone_gap(i, player_position, global_time, offset) >= 0.0;
+        let is_first_outside = |i: usize| (i == 0 || is_inside(i - 1)) && !is_inside(i);
+
+        let last_index = self.last_index.load(Ordering::Relaxed).min(len);
+
+        let i = if is_first_outside(last_index) {
+            last_index
+        } else if last_index < len && is_first_outside(last_index + 1) {
+            last_index + 1
+        } else {
+            let (mut low, mut high) = (0, len);
+
+            while low < high {
+                let mid = (low + high) / 2;
+
+                if is_inside(mid) {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+
+            low
+        };
+
+        self.last_index.store(i, Ordering::Relaxed);
+        i
+    }
+
+    // Positive when the sample is inside the past light cone, zero on it and negative outside
+    fn light_cone_gap(&self, index: usize, player_position: &Position, global_time: f64, offset: f64) -> f64 {
+        let (position, time) = self.samples[index];
+
+        global_time - (position - player_position.0).length() as f64 - (time as f64 * SPEED_OF_LIGHT as f64 + offset)
+    }
+
+    // The segment between two samples, each given as (index, offset)
+    fn segment(&self, start: (usize, f64), end: (usize, f64), player_position: &Position) -> Segment {
+        let event = |(index, offset): (usize, f64)| {
+            let (position, time) = self.samples[index];
+            (position - player_position.0, time as f64 * SPEED_OF_LIGHT as f64 + offset)
+        };
+
+        Segment {
+            start: event(start),
+            end: event(end),
+            start_tangent: self.tangent(start.0),
+            end_tangent: self.tangent(end.0),
+            interpolation: self.interpolation
+        }
+    }
+
+    // Catmull-Rom tangent at a sample, one sided at the ends of non-periodic paths
+    fn tangent(&self, index: usize) -> Vec2 {
+        let last = self.samples.len() - 1;
+        let period = self.period() / SPEED_OF_LIGHT;
+
+        let (before, before_time) = match index {
+            0 if self.periodic && last > 1 => (self.samples[last - 1].0, self.samples[last - 1].1 - period),
+            0 => self.samples[0],
+            _ => self.samples[index - 1]
+        };
+
+        let (after, after_time) = match index {
+            _ if index == last && self.periodic && last > 1 => (self.samples[1].0, self.samples[1].1 + period),
+            _ if index == last => self.samples[last],
+            _ => self.samples[index + 1]
+        };
+
+        if after_time == before_time {
+            return Vec2::ZERO;
+        }
+
+        (after - before) / ((after_time - before_time) * SPEED_OF_LIGHT)
+    }
+}
+
+impl Segment {
+    fn position(&self, s: f64) -> Vec2 {
+        match self.interpolation {
+            Interpolation::Linear => self.start.0 + s as f32 * (self.end.0 - self.start.0),
+            Interpolation::CatmullRom => {
+                let s = s as f32;
+                let duration = (self.end.1 - self.start.1) as f32;
+
+                (2.0 * s.powi(3) - 3.0 * s.powi(2) + 1.0) * self.start.0
+                    + (s.powi(3) - 2.0 * s.powi(2) + s) * duration * self.start_tangent
+                    + (-2.0 * s.powi(3) + 3.0 * s.powi(2)) * self.end.0
+                    + (s.powi(3) - s.powi(2)) * duration * self.end_tangent
+            }
+        }
+    }
+
+    // d(position)/ds
+    fn derivative(&self, s: f64) -> Vec2 {
+        match self.interpolation {
+            Interpolation::Linear => self.end.0 - self.start.0,
+            Interpolation::CatmullRom => {
+                let s = s as f32;
+                let duration = (self.end.1 - self.start.1) as f32;
+
+                (6.0 * s.powi(2) - 6.0 * s) * self.start.0
+                    + (3.0 * s.powi(2) - 4.0 * s + 1.0) * duration * self.start_tangent
+                    + (-6.0 * s.powi(2) + 6.0 * s) * self.end.0
+                    + (3.0 * s.powi(2) - 2.0 * s) * duration * self.end_tangent
+            }
+        }
+    }
+
+    fn time(&self, s: f64) -> f64 {
+        self.start.1 + s * (self.end.1 - self.start.1)
+    }
+
+    // In pixels per second, zero along the instant wrap around of a periodic path
+    fn velocity(&self, s: f64) -> Vec2 {
+        let duration = self.end.1 - self.start.1;
+        if duration <= 0.0 {
+            return Vec2::ZERO;
+        }
+
+        self.derivative(s) / duration as f32 * SPEED_OF_LIGHT
+    }
+
+    // Proper time (in seconds) from the start of the segment to s, the integral of 1/gamma over
+    // coordinate time by Simpson's rule. Superluminal stretches don't age at all
+    fn proper_time(&self, s: f64) -> f64 {
+        const STEPS: usize = 8;
+
+        let duration = self.end.1 - self.start.1;
+        if duration <= 0.0 || s <= 0.0 {
+            return 0.0;
+        }
+
+        let inverse_gamma = |s: f64| (1.0 - (self.derivative(s).length() as f64 / duration).powi(2)).max(0.0).sqrt();
+        let step = s / STEPS as f64;
+
+        let sum: f64 = (0..=STEPS).map(|i| {
+            let weight = if i == 0 || i == STEPS { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+            weight * inverse_gamma(i as f64 * step)
+        }).sum();
+
+        sum * step / 3.0 * duration / SPEED_OF_LIGHT as f64
+    }
+
+    // Solves for the s where the time left for light to reach the player equals the distance
+    // to them. The gap is positive at s = 0 and negative at s = 1, so a root is bracketed
+    fn past_light_cone_event(&self, global_time: f64) -> Sighting {
+        if self.end.1 <= self.start.1 {
+            return Sighting::At(self.end.0, self.end.1);
+        }
+
+        let s = find_light_cone_root(|s| {
+            let position = self.position(s);
+            let distance = position.length();
+            let gap = global_time - self.time(s) - distance as f64;
+            let slope = -(self.end.1 - self.start.1)
+                - if distance > 0.0 { (position.dot(self.derivative(s)) / distance) as f64 } else { 0.0 };
+
+            (gap, slope)
+        }, 0.0, 1.0);
+
+        Sighting::At(self.position(s), self.time(s))
+    }
+}
+
+// Finds where a decreasing function crosses zero given f(low) >= 0 >= f(high), where `f` returns
+// its value and slope. Newton's method is kept inside the bracket by falling back to bisection
+pub fn find_light_cone_root(f: impl Fn(f64) -> (f64, f64), mut low: f64, mut high: f64) -> f64 {
+    let mut x = (low + high) / 2.0;
+
+    for _ in 0..LIGHT_CONE_MAX_ITERATIONS {
+        let (value, slope) = f(x);
+        if value.abs() < LIGHT_CONE_TOLERANCE {
+            break;
+        }
+
+        if value > 0.0 {
+            low = x;
+        } else {
+            high = x;
+        }
+
+        let newton = x - value / slope;
+        x = if slope < 0.0 && newton > low && newton < high { newton } else { (low + high) / 2.0 };
+    }
+
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const LOOKUPS: usize = 2_000;
+
+    // A slow spiral sampled every 1/60th of a second, like a recorded worldline
+    fn recorded_samples() -> Vec<(Vec2, f32)> {
+        (0..1_000).map(|i| {
+            let time = i as f32 / 60.0;
+            (Vec2::new(time.cos(), time.sin()) * (100.0 + time), time)
+        }).collect()
+    }
+
+    // An orbit at about a third of light speed, its last sample back where it started
+    fn orbit_samples() -> Vec<(Vec2, f32)> {
+        (0..=64).map(|i| {
+            let angle = i as f32 / 64.0 * std::f32::consts::TAU;
+            (Vec2::new(angle.cos(), angle.sin()) * 2_000.0, i as f32 / 64.0 * 10.0)
+        }).collect()
+    }
+
+    // What first_outside_light_cone finds, one sample at a time
+    fn linear_first_outside(path: &Path, player_position: &Position, global_time: f64, offset: f64) -> usize {
+        (0..path.samples.len())
+            .find(|&i| path.light_cone_gap(i, player_position, global_time, offset) < 0.0)
+            .unwrap_or(path.samples.len())
+    }
+
+    fn check_lookups(samples: Vec<(Vec2, f32)>, periodic: bool, times: impl Iterator<Item = f64>) {
+        let player_position = Position(Vec2::new(50.0, -20.0));
+        let new_path = || Path::new(samples.clone(), periodic, Interpolation::Linear, SuperluminalPolicy::Reject).unwrap();
+        let path = new_path();
+
+        for time in times {
+            let ct = time * SPEED_OF_LIGHT as f64;
+            let offset = path.light_cone_offset(&player_position, ct);
+
+            assert_eq!(
+                path.first_outside_light_cone(&player_position, ct, offset),
+                linear_first_outside(&path, &player_position, ct, offset),
+                "index at time {}", time
+            );
+
+            // A path that has never been looked up has nothing cached
+            assert_eq!(
+                path.past_light_cone_event(&player_position, time),
+                new_path().past_light_cone_event(&player_position, time),
+                "event at time {}", time
+            );
+        }
+    }
+
+    fn sequential_times(duration: f64) -> impl Iterator<Item = f64> {
+        (0..LOOKUPS).map(move |i| i as f64 * duration / LOOKUPS as f64)
+    }
+
+    fn random_times(duration: f64) -> impl Iterator<Item = f64> {
+        let mut rng = StdRng::seed_from_u64(29);
+        (0..LOOKUPS).map(move |_| rng.gen_range(-1.0..duration))
+    }
+
+    #[test]
+    fn new_applies_the_superluminal_policy() {
+        let too_fast = vec![(Vec2::ZERO, 0.0), (Vec2::new(SPEED_OF_LIGHT * 2.0, 0.0), 1.0)];
+        let new = |samples: Vec<(Vec2, f32)>, superluminal| Path::new(samples, false, Interpolation::Linear, superluminal);
+
+        assert_eq!(new(Vec::new(), SuperluminalPolicy::Warn).unwrap_err(), PathError::NoSamples);
+        assert!(matches!(new(too_fast.clone(), SuperluminalPolicy::Reject), Err(PathError::Superluminal(segments)) if segments.len() == 1));
+        assert_eq!(new(too_fast.clone(), SuperluminalPolicy::Warn).unwrap().superluminal_segments().len(), 1);
+        assert!(new(too_fast, SuperluminalPolicy::Clamp).unwrap().superluminal_segments().is_empty());
+    }
+
+    #[test]
+    fn sequential_lookups_match_a_linear_scan() {
+        check_lookups(recorded_samples(), false, sequential_times(20.0));
+    }
+
+    #[test]
+    fn random_lookups_match_a_linear_scan() {
+        check_lookups(recorded_samples(), false, random_times(20.0));
+    }
+
+    #[test]
+    fn periodic_lookups_match_a_linear_scan() {
+        check_lookups(orbit_samples(), true, sequential_times(35.0));
+        check_lookups(orbit_samples(), true, random_times(35.0));
+    }
+
+    #[test]
+    fn periodic_paths_can_start_late() {
+        let player_position = Position(Vec2::new(50.0, -20.0));
+        let new_path = || Path::new(orbit_samples(), true, Interpolation::CatmullRom, SuperluminalPolicy::Reject).unwrap();
+        let path = new_path();
+        let late = new_path().starting_at(3.0);
+
+        assert_eq!(late.period(), path.period());
+
+        for time in random_times(35.0) {
+            assert!(late.position_at(time + 3.0).distance(path.position_at(time)) < 1e-2, "position at time {}", time);
+            assert!((late.proper_time_at(time + 3.0) - path.proper_time_at(time)).abs() < 1e-4, "proper time at time {}", time);
+
+            match (late.past_light_cone_event(&player_position, time + 3.0), path.past_light_cone_event(&player_position, time)) {
+                (Sighting::At(late_position, late_time), Sighting::At(position, event_time)) => {
+                    assert!(late_position.distance(position) < 1e-2, "event at time {}", time);
+                    assert!((late_time - event_time - 3.0 * SPEED_OF_LIGHT as f64).abs() < 1e-2, "event at time {}", time);
+                },
+                sightings => panic!("Seen differently at time {}: {:?}", time, sightings)
+            }
+        }
+    }
+}
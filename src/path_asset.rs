@@ -0,0 +1,328 @@
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    reflect::TypeUuid,
+    utils::HashSet
+};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+// Paths can be written as RON, JSON or CSV files in assets/paths. RON and JSON share the
+// layout of PathAsset, e.g.
+//
+//     (
+//         periodic: true,
+//         interpolation: catmull_rom,
+//         before_start: rest, // or despawn, only used by non-periodic paths
+//         after_end: despawn,
+//         superluminal: clamp, // or warn (the default) or reject
+//         color: (1.0, 0.0, 0.0),
+//         size: 10.0,
+//         samples: [(x: 100.0, y: 0.0, t: 0.0), (x: 0.0, y: 100.0, t: 15.0), ...],
+//     )
+//
+// while CSV files list one `x,y,t` sample per line with the metadata in `# key = value` comments:
+//
+//     # periodic = true
+//     # interpolation = catmull_rom
+//     # after_end = despawn
+//     # superluminal = reject
+//     # color = 1.0, 0.0, 0.0
+//     # size = 10.0
+//     x,y,t
+//     100.0,0.0,0.0
+//     ...
+
+pub struct PathAssetPlugin;
+
+impl Plugin for PathAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_asset::<PathAsset>()
+            .init_asset_loader::<PathAssetLoader>()
+            .add_system(apply_path_assets);
+    }
+}
+
+// ----------------------------------<< Assets >>----------------------------------
+
+#[derive(Serialize, Deserialize, TypeUuid, Debug, Clone)]
+#[uuid = "5b3c8f0e-7a51-4d0c-9a57-2f6e41c3d8b9"]
+pub struct PathAsset {
+    #[serde(default = "default_periodic")]
+    pub periodic: bool,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    #[serde(default)]
+    pub before_start: PathEnd,
+    #[serde(default)]
+    pub after_end: PathEnd,
+    #[serde(default)]
+    pub superluminal: SuperluminalPolicy,
+    #[serde(default = "default_color")]
+    pub color: (f32, f32, f32),
+    #[serde(default = "default_size")]
+    pub size: f32,
+    pub samples: Vec<PathSample>
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct PathSample {
+    pub x: f32,
+    pub y: f32,
+    pub t: f32
+}
+
+fn default_periodic() -> bool { true }
+fn default_color() -> (f32, f32, f32) { (1.0, 1.0, 1.0) }
+fn default_size() -> f32 { 10.0 }
+
+impl PathAsset {
+    pub fn to_path(&self) -> Result<Path, PathError> {
+        Ok(Path::new(
+            self.samples.iter().map(|sample| (Vec2::new(sample.x, sample.y), sample.t)).collect(),
+            self.periodic,
+            self.interpolation,
+            self.superluminal
+        )?.with_ends(self.before_start, self.after_end))
+    }
+
+    pub fn color(&self) -> Color {
+        Color::rgb(self.color.0, self.color.1, self.color.2)
+    }
+
+    fn from_csv(text: &str) -> Result<Self, Error> {
+        let mut asset = PathAsset {
+            periodic: default_periodic(),
+            interpolation: Interpolation::default(),
+            before_start: PathEnd::default(),
+            after_end: PathEnd::default(),
+            superluminal: SuperluminalPolicy::default(),
+            color: default_color(),
+            size: default_size(),
+            samples: Vec::new()
+        };
+
+        for (line_number, line) in text.lines().enumerate().map(|(i, line)| (i + 1, line.trim())) {
+            if line.is_empty() || line.eq_ignore_ascii_case("x,y,t") {
+                continue;
+            }
+
+            if let Some(comment) = line.strip_prefix('#') {
+                let Some((key, value)) = comment.split_once('=') else { continue };
+                let value = value.trim();
+
+                match key.trim() {
+                    "periodic" => asset.periodic = value.parse()
+                        .map_err(|err| Error::msg(format!("line {}: {} ({:?})", line_number, err, value)))?,
+                    "interpolation" => asset.interpolation = match value {
+                        "linear" => Interpolation::Linear,
+                        "catmull_rom" => Interpolation::CatmullRom,
+                        _ => return Err(Error::msg(format!("line {}: unknown interpolation \"{}\"", line_number, value)))
+                    },
+                    "before_start" => asset.before_start = parse_path_end(value, line_number)?,
+                    "after_end" => asset.after_end = parse_path_end(value, line_number)?,
+                    "superluminal" => asset.superluminal = match value {
+                        "warn" => SuperluminalPolicy::Warn,
+                        "clamp" => SuperluminalPolicy::Clamp,
+                        "reject" => SuperluminalPolicy::Reject,
+                        _ => return Err(Error::msg(format!("line {}: unknown superluminal policy \"{}\"", line_number, value)))
+                    },
+                    "size" => asset.size = value.parse()
+                        .map_err(|err| Error::msg(format!("line {}: {} ({:?})", line_number, err, value)))?,
+                    "color" => {
+                        let channels = parse_floats(value, line_number)?;
+                        if channels.len() != 3 {
+                            return Err(Error::msg(format!("line {}: color needs 3 channels, found {}", line_number, channels.len())));
+                        }
+
+                        asset.color = (channels[0], channels[1], channels[2]);
+                    },
+                    key => warn!("Unknown path metadata key \"{}\" on line {}", key, line_number)
+                }
+
+                continue;
+            }
+
+            let values = parse_floats(line, line_number)?;
+            if values.len() != 3 {
+                return Err(Error::msg(format!("line {}: expected x,y,t but found {} values", line_number, values.len())));
+            }
+
+            asset.samples.push(PathSample { x: values[0], y: values[1], t: values[2] });
+        }
+
+        Ok(asset)
+    }
+}
+
+fn parse_path_end(text: &str, line_number: usize) -> Result<PathEnd, Error> {
+    match text {
+        "rest" => Ok(PathEnd::Rest),
+        "despawn" => Ok(PathEnd::Despawn),
+        _ => Err(Error::msg(format!("line {}: unknown path end \"{}\"", line_number, text)))
+    }
+}
+
+fn parse_floats(text: &str, line_number: usize) -> Result<Vec<f32>, Error> {
+    text.split(',')
+        .map(|value| value.trim().parse::<f32>()
+            .map_err(|err| Error::msg(format!("line {}: {} ({:?})", line_number, err, value.trim())))
+        )
+        .collect()
+}
+
+// ----------------------------------<< Loaders >>----------------------------------
+
+#[derive(Default)]
+pub struct PathAssetLoader;
+
+impl AssetLoader for PathAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let extension = load_context.path().extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+            let asset: PathAsset = match extension {
+                "ron" => ron::de::from_bytes(bytes)?,
+                "json" => serde_json::from_slice(bytes)?,
+                "csv" => PathAsset::from_csv(std::str::from_utf8(bytes)?)?,
+                _ => return Err(Error::msg(format!("Unsupported path file extension \"{}\"", extension)))
+            };
+
+            if let Err(err) = asset.to_path() {
+                return Err(Error::msg(format!("Path file {:?} can't be used: {}", load_context.path(), err)));
+            }
+
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["path.ron", "path.json", "path.csv"]
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// (Re)builds the Path of every entity holding a Handle<PathAsset> when the file is loaded or hot reloaded.
+// The file's colour is the one Doppler shaded bodies are tinted from, so it isn't painted over
+#[allow(clippy::type_complexity)]
+fn apply_path_assets(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<PathAsset>>,
+    path_assets: Res<Assets<PathAsset>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    settings: Res<Settings>,
+    mut query_paths: Query<(Entity, &Handle<PathAsset>, ChangeTrackers<Handle<PathAsset>>, &Handle<ColorMaterial>, Option<&mut DopplerShaded>, &mut Transform)>
+) {
+    let mut changed_handles = HashSet::new();
+
+    for event in asset_events.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                changed_handles.insert(handle.clone_weak());
+            },
+            AssetEvent::Removed { .. } => {}
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    for (entity, path_handle, path_handle_tracker, material_handle, doppler_shaded, mut transform) in query_paths.iter_mut() {
+        if !changed_handles.contains(path_handle) && !path_handle_tracker.is_added() {
+            continue;
+        }
+
+        // Not loaded yet, the Created event will come through later
+        let Some(path_asset) = path_assets.get(path_handle) else { continue };
+
+        // The loader already turned away the ones that can't be made
+        let Ok(path) = path_asset.to_path() else { continue };
+        commands.entity(entity).insert(path);
+
+        if let Some(mut doppler_shaded) = doppler_shaded {
+            doppler_shaded.0 = path_asset.color();
+        }
+
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.color = path_asset.color();
+        }
+
+        transform.scale = Vec3::new(path_asset.size, path_asset.size, 0.0);
+        transform.translation.z = settings.render_depths.path + rng.gen_range(-0.001..0.001);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv_error(text: &str) -> String {
+        PathAsset::from_csv(text).expect_err("should have been turned away").to_string()
+    }
+
+    #[test]
+    fn csv_reads_metadata_and_samples() {
+        let asset = PathAsset::from_csv("\
+            # periodic = false
+            # interpolation = catmull_rom
+            # before_start = despawn
+            # after_end = rest
+            # superluminal = reject
+            # color = 1.0, 0.5, 0.0
+            # size = 20.0
+            # just a comment
+            x,y,t
+
+            100.0,0.0,0.0
+            0.0, 100.0, 15.0
+        ").unwrap();
+
+        assert!(!asset.periodic);
+        assert_eq!(asset.interpolation, Interpolation::CatmullRom);
+        assert_eq!(asset.before_start, PathEnd::Despawn);
+        assert_eq!(asset.after_end, PathEnd::Rest);
+        assert_eq!(asset.superluminal, SuperluminalPolicy::Reject);
+        assert_eq!(asset.color, (1.0, 0.5, 0.0));
+        assert_eq!(asset.size, 20.0);
+        assert_eq!(asset.samples.len(), 2);
+        assert_eq!((asset.samples[1].x, asset.samples[1].y, asset.samples[1].t), (0.0, 100.0, 15.0));
+    }
+
+    #[test]
+    fn csv_defaults_without_metadata() {
+        let asset = PathAsset::from_csv("0,0,0\n10,0,1").unwrap();
+
+        assert!(asset.periodic);
+        assert_eq!(asset.color, default_color());
+        assert_eq!(asset.size, default_size());
+        assert_eq!(asset.samples.len(), 2);
+    }
+
+    #[test]
+    fn csv_errors_name_the_line() {
+        let error = csv_error("x,y,t\n0,0,0\n10,zero,1");
+        assert!(error.starts_with("line 3:"), "{}", error);
+
+        let error = csv_error("0,0,0\n10,0");
+        assert!(error.starts_with("line 2: expected x,y,t but found 2 values"), "{}", error);
+
+        let error = csv_error("# periodic = sometimes");
+        assert!(error.starts_with("line 1:"), "{}", error);
+
+        let error = csv_error("0,0,0\n# interpolation = cubic");
+        assert!(error.starts_with("line 2: unknown interpolation"), "{}", error);
+
+        let error = csv_error("\n\n# color = 1.0, 0.0");
+        assert!(error.starts_with("line 3: color needs 3 channels, found 2"), "{}", error);
+
+        let error = csv_error("# after_end = wrap");
+        assert!(error.starts_with("line 1: unknown path end"), "{}", error);
+    }
+}
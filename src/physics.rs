@@ -0,0 +1,359 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use std::f32::consts::{PI, TAU};
+
+use super::*;
+
+// The player's motion and clocks. With a fixed timestep the physics steps TIMESTEP at a time on
+// the wall clock, otherwise every update is exactly one step, which is what headless runs want.
+// Needs the Settings, see src/settings.rs. The player coasts until something fills in PlayerInput,
+// like BindingsPlugin or ReplayPlugin. Other plugins add their own systems to PhysicsStage::Step
+// with the PhysicsSteps run criteria and order them against PhysicsSystem
+pub struct SpacetimePhysicsPlugin {
+    pub fixed_timestep: bool
+}
+
+impl Plugin for SpacetimePhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        let physics_step = SystemSet::new()
+            .with_run_criteria(run_physics_steps.label(PhysicsSteps))
+            .with_system(store_previous_step.before(PhysicsSystem::Move))
+            .with_system(move_player.label(PhysicsSystem::Move))
+            .with_system(record_worldline.after(PhysicsSystem::Move));
+
+        app
+            .insert_resource(GlobalTime(0.0))
+            .insert_resource(LocalTime(0.0))
+            .insert_resource(PhysicsClock::new(self.fixed_timestep))
+            .init_resource::<PlayerInput>()
+            .init_resource::<PreviousStep>()
+            .init_resource::<RenderedPlayer>()
+            .init_resource::<PlayerReset>()
+            .init_resource::<Worldline>()
+            .add_stage_after(CoreStage::PreUpdate, PhysicsStage::Step, SystemStage::parallel())
+            .add_stage_after(PhysicsStage::Step, PhysicsStage::Interpolate, SystemStage::parallel())
+            .add_startup_system_to_stage(StartupStage::PreStartup, spawn_player)
+            .add_system_to_stage(PhysicsStage::Step, reset_player.before(store_previous_step))
+            .add_system_set_to_stage(PhysicsStage::Step, physics_step)
+            .add_system_to_stage(PhysicsStage::Interpolate, interpolate_rendered_player.label(PhysicsSystem::Interpolate));
+    }
+}
+
+// Between reading input and everything that draws, so the frame always sees the latest step
+#[derive(StageLabel)]
+pub enum PhysicsStage {
+    // Moves the player, and anything else that has to see every step, TIMESTEP at a time
+    Step,
+    // Works out where the player is drawn this frame, see RenderedPlayer
+    Interpolate
+}
+
+// Runs a set once for every physics step this frame, for systems added to PhysicsStage::Step that
+// have to see every step, e.g.
+//
+//     SystemSet::new().with_run_criteria(PhysicsSteps).with_system(check_objectives.after(PhysicsSystem::Move))
+#[derive(RunCriteriaLabel)]
+pub struct PhysicsSteps;
+
+#[derive(SystemLabel)]
+pub enum PhysicsSystem {
+    // Takes the step with PlayerInput, which has to be filled in before this. PreviousStep holds
+    // the player from before it
+    Move,
+    // Sets RenderedPlayer, in PhysicsStage::Interpolate
+    Interpolate
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+// Decides how many physics steps are taken each frame, see run_physics_steps
+#[derive(Resource)]
+pub struct PhysicsClock {
+    // Seconds of simulation per second on the wall clock. Steps are always TIMESTEP long, so this
+    // only changes how often they're taken, see src/time_control.rs
+    pub speed: f32,
+    // Nothing but extra steps are taken
+    pub paused: bool,
+    // Taken on the next frame on top of the wall clock's, e.g. to catch a replay up to where it
+    // was scrubbed to
+    pub extra_steps: usize,
+    // Steps left before it stops, e.g. at the end of a replay
+    pub step_limit: Option<usize>,
+    fixed_timestep: bool,
+    // Simulation time the steps haven't caught up with yet
+    accumulator: f32,
+    stepping: bool
+}
+
+impl PhysicsClock {
+    pub fn new(fixed_timestep: bool) -> Self {
+        PhysicsClock { speed: 1.0, paused: false, extra_steps: 0, step_limit: None, fixed_timestep, accumulator: 0.0, stepping: false }
+    }
+
+    // How far the frame is into the next step, from 0 to 1. While paused it's the latest step,
+    // so that stepping one at a time shows each step as it is
+    pub fn overstep(&self) -> f32 {
+        if self.paused {
+            return 1.0;
+        }
+
+        (self.accumulator / TIMESTEP).clamp(0.0, 1.0)
+    }
+}
+
+// What the player is asking for this step, filled in from their Actions or a replay
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerInput {
+    // From -1 to 1 on each axis
+    pub thrust: Vec2,
+    pub brake: bool,
+    // Along the ship's heading, from -1 (full reverse) to 1
+    pub forward: f32,
+    // A heading to point the ship at straight away
+    pub aim: Option<f32>,
+    // From -1 (clockwise) to 1, in turn rates
+    pub turn: f32
+}
+
+// The player at the start of the latest physics step
+#[derive(Resource, Default)]
+pub struct PreviousStep(pub PlayerState);
+
+// Where the player is drawn this frame, blended between the last two physics steps by how far the
+// frame is into the next one so that motion stays smooth when frames don't line up with steps.
+// Everything that only draws reads this rather than the physics state, which is also what lets
+// src/rewind.rs show the scene from an earlier event
+#[derive(Resource, Default)]
+pub struct RenderedPlayer(pub PlayerState);
+
+// Puts the player here, with its clocks, before the next physics step, e.g. when a level starts.
+// The worldline carries on from `history` when there is one, as when a saved game is loaded
+#[derive(Resource, Default)]
+pub struct PlayerReset {
+    pub state: Option<PlayerState>,
+    pub history: Vec<PlayerState>
+}
+
+// The player at the end of every physics step since the level started, oldest first
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct Worldline(pub Vec<PlayerState>);
+
+impl Worldline {
+    // Blended between the steps either side of the coordinate time, clamped to the ones recorded
+    pub fn at(&self, global_time: f32) -> Option<PlayerState> {
+        let next = self.0.partition_point(|state| state.global_time < global_time);
+
+        match (next.checked_sub(1).and_then(|previous| self.0.get(previous)), self.0.get(next)) {
+            (Some(previous), Some(next)) => {
+                let t = (global_time - previous.global_time) / (next.global_time - previous.global_time);
+                Some(previous.lerp(next, t))
+            },
+            (previous, next) => previous.or(next).copied()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PlayerState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub global_time: f32,
+    pub local_time: f32,
+    pub heading: f32
+}
+
+impl PlayerState {
+    pub fn lerp(&self, other: &PlayerState, t: f32) -> PlayerState {
+        // The short way around
+        let turn = (other.heading - self.heading + PI).rem_euclid(TAU) - PI;
+
+        PlayerState {
+            position: self.position.lerp(other.position, t),
+            velocity: self.velocity.lerp(other.velocity, t),
+            global_time: self.global_time + (other.global_time - self.global_time) * t,
+            local_time: self.local_time + (other.local_time - self.local_time) * t,
+            heading: (self.heading + turn * t).rem_euclid(TAU)
+        }
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// The only part of the world the physics needs, so it runs before everything else is set up
+fn spawn_player(mut commands: Commands) {
+    commands.spawn((Player, Position(Vec2::new(0.0, 0.0)), Velocity(Vec2::new(0.0, 0.0)), Heading(0.0)));
+}
+
+// Outside of the fixed timestep, so the player is moved even on frames without a step
+fn reset_player(
+    mut player_reset: ResMut<PlayerReset>,
+    mut query_player: Query<(&mut Position, &mut Velocity, &mut Heading), With<Player>>,
+    mut global_time: ResMut<GlobalTime>,
+    mut local_time: ResMut<LocalTime>,
+    mut previous_step: ResMut<PreviousStep>,
+    mut rendered_player: ResMut<RenderedPlayer>,
+    mut worldline: ResMut<Worldline>
+) {
+    let Some(state) = player_reset.state.take() else { return };
+    let (mut position, mut velocity, mut heading) = query_player.single_mut();
+
+    position.0 = state.position;
+    velocity.0 = state.velocity;
+    heading.0 = state.heading;
+    global_time.0 = state.global_time;
+    local_time.0 = state.local_time;
+    previous_step.0 = state;
+    rendered_player.0 = state;
+    worldline.0 = std::mem::take(&mut player_reset.history);
+
+    if worldline.0.is_empty() {
+        worldline.0.push(state);
+    }
+}
+
+fn record_worldline(
+    query_player: Query<(&Position, &Velocity, &Heading), With<Player>>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    mut worldline: ResMut<Worldline>
+) {
+    let (Position(position), Velocity(velocity), Heading(heading)) = query_player.single();
+
+    worldline.0.push(PlayerState { position: *position, velocity: *velocity, global_time: global_time.0, local_time: local_time.0, heading: *heading });
+}
+
+fn store_previous_step(
+    query_player: Query<(&Position, &Velocity, &Heading), With<Player>>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    mut previous_step: ResMut<PreviousStep>
+) {
+    let (Position(position), Velocity(velocity), Heading(heading)) = query_player.single();
+
+    previous_step.0 = PlayerState { position: *position, velocity: *velocity, global_time: global_time.0, local_time: local_time.0, heading: *heading };
+}
+
+// Like bevy's FixedTimestep, but scaled by the clock's speed, with pausing and with room for extra
+// steps. The set runs again for as long as this says YesAndCheckAgain
+fn run_physics_steps(time: Res<Time>, mut clock: ResMut<PhysicsClock>) -> ShouldRun {
+    if !clock.stepping && !clock.paused {
+        clock.accumulator += if clock.fixed_timestep { time.delta_seconds() * clock.speed } else { TIMESTEP };
+    }
+
+    // Holding on to no more than a step keeps it from rushing to catch up when the limit goes
+    if clock.step_limit == Some(0) {
+        clock.accumulator = clock.accumulator.min(TIMESTEP);
+        clock.extra_steps = 0;
+        clock.stepping = false;
+        return ShouldRun::No;
+    }
+
+    if clock.extra_steps > 0 {
+        clock.extra_steps -= 1;
+    } else if clock.accumulator >= TIMESTEP {
+        clock.accumulator -= TIMESTEP;
+    } else {
+        clock.stepping = false;
+        return ShouldRun::No;
+    }
+
+    if let Some(step_limit) = clock.step_limit.as_mut() {
+        *step_limit -= 1;
+    }
+
+    clock.stepping = true;
+    ShouldRun::YesAndCheckAgain
+}
+
+// Lags up to a step behind the physics, which is what lets it blend towards the latest step
+fn interpolate_rendered_player(
+    physics_clock: Res<PhysicsClock>,
+    query_player: Query<(&Position, &Velocity, &Heading), With<Player>>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    previous_step: Res<PreviousStep>,
+    mut rendered_player: ResMut<RenderedPlayer>
+) {
+    let (Position(position), Velocity(velocity), Heading(heading)) = query_player.single();
+    let latest_step = PlayerState { position: *position, velocity: *velocity, global_time: global_time.0, local_time: local_time.0, heading: *heading };
+    let overstep = if physics_clock.fixed_timestep { physics_clock.overstep() } else { 1.0 };
+
+    rendered_player.0 = previous_step.0.lerp(&latest_step, overstep);
+}
+
+fn move_player(
+    player_input: Res<PlayerInput>,
+    mut query_player: Query<(&mut Position, &mut Velocity, &mut Heading), With<Player>>,
+    mut global_time: ResMut<GlobalTime>,
+    mut local_time: ResMut<LocalTime>,
+    settings: Res<Settings>,
+    current_level: Option<Res<CurrentLevel>>
+) {
+    let PlayerSettings { max_speed, acceleration_x, acceleration_y, braking, friction, turn_rate, .. } = settings.player;
+    let bounds = current_level.as_ref().and_then(|level| level.asset.bounds.as_ref()).unwrap_or(&settings.bounds);
+
+    let (mut player_position, mut player_velocity, mut player_heading) = query_player.single_mut();
+
+    // Turning, in the player's own frame
+    if let Some(aim) = player_input.aim {
+        player_heading.0 = aim;
+    }
+
+    player_heading.0 = (player_heading.0 + player_input.turn * turn_rate * TIMESTEP).rem_euclid(TAU);
+
+    // Acceleration changing velocity
+    let mut thrust = player_input.thrust;
+
+    if player_input.forward != 0.0 {
+        thrust += player_input.forward * heading_direction(player_heading.0, &player_velocity);
+    }
+
+    let mut dx = thrust.x * acceleration_x * TIMESTEP;
+    let mut dy = thrust.y * acceleration_y * TIMESTEP;
+
+    // Adds the friction if the player is pressing space
+    if player_input.brake {
+        if player_velocity.0.length() < max_speed / 1000.0 {
+            player_velocity.0.x = 0.0;
+            player_velocity.0.y = 0.0;
+        }
+
+        dx -= braking * player_velocity.0.x;
+        dy -= braking * player_velocity.0.y;
+    } else if dx == 0.0 && dy == 0.0 {
+        dx -= friction * player_velocity.0.x;
+        dy -= friction * player_velocity.0.y;
+    }
+
+    // Boundry detection
+    if player_position.0.x < bounds.left { 
+        dx -= player_position.0.x - bounds.left;
+    }
+
+    if player_position.0.x > bounds.right {
+        dx -= player_position.0.x - bounds.right;
+    }
+
+    if player_position.0.y < bounds.lower { 
+        dy -= player_position.0.y - bounds.lower;
+    }
+
+    if player_position.0.y > bounds.upper {
+        dy -= player_position.0.y - bounds.upper;
+    }
+
+    player_velocity.0.x += dx;
+    player_velocity.0.y += dy;
+
+    // caps the speed
+    if player_velocity.0.length() > max_speed {
+        player_velocity.0 = max_speed * player_velocity.0.normalize();
+    }
+
+    // Velocity change position
+    player_position.0.x += player_velocity.0.x * TIMESTEP;
+    player_position.0.y += player_velocity.0.y * TIMESTEP;
+
+    // Update global time
+    global_time.0 += TIMESTEP / (1.0 - player_velocity.0.length().powi(2) / SPEED_OF_LIGHT.powi(2)).sqrt();
+    local_time.0 += TIMESTEP;
+}
@@ -4,6 +4,8 @@
 #![allow(non_upper_case_globals)]
 #![allow(non_snake_case)]
 #![allow(non_camel_case_types)]
+// The generated products keep the generator's `* -1.0` sign terms, so they read like its output
+#![allow(clippy::neg_multiply)]
 
 // Modifed to make work with my project, original: R120 code generator at https://bivector.net/tools.html
 
@@ -12,10 +14,9 @@ use std::ops::{Index,IndexMut,Add,Sub,Mul,BitAnd,BitOr,BitXor,Not};
 
 type float_t = f32;
 
-// use std::f64::consts::PI;
-const PI: float_t = 3.14159265358979323846;
+const PI: float_t = std::f32::consts::PI;
 
-const basis: &'static [&'static str] = &[ "1","e1","e2","e3","e12","e13","e23","e123" ];
+const basis: &[&str] = &[ "1","e1","e2","e3","e12","e13","e23","e123" ];
 const basis_count: usize = basis.len();
 
 #[derive(Default,Debug,Clone,Copy,PartialEq)]
@@ -38,24 +39,24 @@ impl R120 {
 }
 
 // basis vectors are available as global constants.
-const e1: R120 = R120::new(1.0, 1);
-const e2: R120 = R120::new(1.0, 2);
-const e3: R120 = R120::new(1.0, 3);
-const e12: R120 = R120::new(1.0, 4);
-const e13: R120 = R120::new(1.0, 5);
-const e23: R120 = R120::new(1.0, 6);
-const e123: R120 = R120::new(1.0, 7);
+pub const e1: R120 = R120::new(1.0, 1);
+pub const e2: R120 = R120::new(1.0, 2);
+pub const e3: R120 = R120::new(1.0, 3);
+pub const e12: R120 = R120::new(1.0, 4);
+pub const e13: R120 = R120::new(1.0, 5);
+pub const e23: R120 = R120::new(1.0, 6);
+pub const e123: R120 = R120::new(1.0, 7);
 
 impl Index<usize> for R120 {
     type Output = float_t;
 
-    fn index<'a>(&'a self, index: usize) -> &'a Self::Output {
+    fn index(&self, index: usize) -> &Self::Output {
         &self.mvec[index]
     }
 }
 
 impl IndexMut<usize> for R120 {
-    fn index_mut<'a>(&'a mut self, index: usize) -> &'a mut Self::Output {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.mvec[index]
     }
 }
@@ -64,7 +65,7 @@ impl fmt::Display for R120 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut n = 0;
         let ret = self.mvec.iter().enumerate().filter_map(|(i, &coeff)| {
-            if coeff > 0.00001 || coeff < -0.00001 {
+            if !(-0.00001..=0.00001).contains(&coeff) {
                 n = 1;
                 Some(format!("{}{}", 
                         format!("{:.*}", 7, coeff).trim_end_matches('0').trim_end_matches('.'),
@@ -82,17 +83,17 @@ impl fmt::Display for R120 {
 // Reverse
 // Reverse the order of the basis blades.
 impl R120 {
-    pub fn Reverse(self: Self) -> R120 {
+    pub fn Reverse(self) -> R120 {
         let mut res = R120::zero();
         let a = self;
         res[0]=a[0];
         res[1]=a[1];
         res[2]=a[2];
         res[3]=a[3];
-        res[4]=-a[4];
-        res[5]=-a[5];
-        res[6]=-a[6];
-        res[7]=-a[7];
+        res[4] = -a[4];
+        res[5] = -a[5];
+        res[6] = -a[6];
+        res[7] = -a[7];
         res
     }
 }
@@ -100,14 +101,14 @@ impl R120 {
 // Dual
 // Poincare duality operator.
 impl R120 {
-    pub fn Dual(self: Self) -> R120 {
+    pub fn Dual(self) -> R120 {
         let mut res = R120::zero();
         let a = self;
-        res[0]=-a[7];
-        res[1]=-a[6];
-        res[2]=-a[5];
+        res[0] = -a[7];
+        res[1] = -a[6];
+        res[2] = -a[5];
         res[3]=a[4];
-        res[4]=-a[3];
+        res[4] = -a[3];
         res[5]=a[2];
         res[6]=a[1];
         res[7]=a[0];
@@ -118,14 +119,14 @@ impl R120 {
 impl Not for R120 {
     type Output = R120;
 
-    fn not(self: Self) -> R120 {
+    fn not(self) -> R120 {
         let mut res = R120::zero();
         let a = self;
-        res[0]=-a[7];
-        res[1]=-a[6];
-        res[2]=-a[5];
+        res[0] = -a[7];
+        res[1] = -a[6];
+        res[2] = -a[5];
         res[3]=a[4];
-        res[4]=-a[3];
+        res[4] = -a[3];
         res[5]=a[2];
         res[6]=a[1];
         res[7]=a[0];
@@ -136,16 +137,16 @@ impl Not for R120 {
 // Conjugate
 // Clifford Conjugation
 impl R120 {
-    pub fn Conjugate(self: Self) -> R120 {
+    pub fn Conjugate(self) -> R120 {
         let mut res = R120::zero();
         let a = self;
         res[0]=a[0];
-        res[1]=-a[1];
-        res[2]=-a[2];
-        res[3]=-a[3];
-        res[4]=-a[4];
-        res[5]=-a[5];
-        res[6]=-a[6];
+        res[1] = -a[1];
+        res[2] = -a[2];
+        res[3] = -a[3];
+        res[4] = -a[4];
+        res[5] = -a[5];
+        res[6] = -a[6];
         res[7]=a[7];
         res
     }
@@ -154,17 +155,17 @@ impl R120 {
 // Involute
 // Main involution
 impl R120 {
-    pub fn Involute(self: Self) -> R120 {
+    pub fn Involute(self) -> R120 {
         let mut res = R120::zero();
         let a = self;
         res[0]=a[0];
-        res[1]=-a[1];
-        res[2]=-a[2];
-        res[3]=-a[3];
+        res[1] = -a[1];
+        res[2] = -a[2];
+        res[3] = -a[3];
         res[4]=a[4];
         res[5]=a[5];
         res[6]=a[6];
-        res[7]=-a[7];
+        res[7] = -a[7];
         res
     }
 }
@@ -219,10 +220,10 @@ impl BitAnd for R120 {
         let a = self;
         res[7]=1.0*(a[7]*b[7]);
 		res[6]=1.0*(a[6]*b[7]+a[7]*b[6]);
-		res[5]=-1.0*(a[5]*-1.0*b[7]+a[7]*b[5]*-1.0);
+		res[5] = -1.0*(a[5]*-1.0*b[7]+a[7]*b[5]*-1.0);
 		res[4]=1.0*(a[4]*b[7]+a[7]*b[4]);
 		res[3]=1.0*(a[3]*b[7]+a[5]*-1.0*b[6]-a[6]*b[5]*-1.0+a[7]*b[3]);
-		res[2]=-1.0*(a[2]*-1.0*b[7]+a[4]*b[6]-a[6]*b[4]+a[7]*b[2]*-1.0);
+		res[2] = -1.0*(a[2]*-1.0*b[7]+a[4]*b[6]-a[6]*b[4]+a[7]*b[2]*-1.0);
 		res[1]=1.0*(a[1]*b[7]+a[4]*b[5]*-1.0-a[5]*-1.0*b[4]+a[7]*b[1]);
 		res[0]=1.0*(a[0]*b[7]+a[1]*b[6]-a[2]*-1.0*b[5]*-1.0+a[3]*b[4]+a[4]*b[3]-a[5]*-1.0*b[2]*-1.0+a[6]*b[1]+a[7]*b[0]);
         res
@@ -410,18 +411,18 @@ impl Add<float_t> for R120 {
 // }
 
 impl R120 {
-    pub fn norm(self: Self) -> float_t {
+    pub fn norm(self) -> float_t {
         let scalar_part = (self * self.Conjugate())[0];
 
         scalar_part.abs().sqrt()
     }
 
-    pub fn inorm(self: Self) -> float_t {
+    pub fn inorm(self) -> float_t {
         self.Dual().norm()
     }
 
     // Modified to have 0 normalize to 0
-    pub fn normalized(self: Self) -> Self {
+    pub fn normalized(self) -> Self {
         let norm = self.norm();
         if norm != 0.0 {
             self * (1.0 / self.norm())
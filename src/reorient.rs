@@ -7,23 +7,66 @@ pub struct ReorientPlugin;
 impl Plugin for ReorientPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_system(reorient_angle_markers)
-            .add_system(reorient_points)
-            .add_system(reorient_paths);
+            .init_resource::<ViewFrame>()
+            .add_system(update_view_frame)
+            .add_system(reorient_angle_markers.after(update_view_frame))
+            .add_system(reorient_points.after(update_view_frame))
+            .add_system(reorient_paths.after(update_view_frame))
+            .add_system(reorient_analytic_paths.after(update_view_frame))
+            .add_system(doppler_shade_paths.after(update_view_frame));
     }
 }
 
+// The frame the scene is drawn in. Always from the event the player is at, but moving along with
+// a body rather than the player when one is picked, so it can be seen as that body would see it
+#[derive(Resource, Default)]
+pub struct ViewFrame {
+    pub body: Option<Entity>,
+    // Of the body when the light now reaching the player left it, or of the player
+    pub velocity: Vec2
+}
+
+fn update_view_frame(
+    rendered_player: Res<RenderedPlayer>,
+    mut view_frame: ResMut<ViewFrame>,
+    query_bodies: Query<(Option<&Path>, Option<&AnalyticPath>)>
+) {
+    let player_position = &Position(rendered_player.0.position);
+    let global_time = rendered_player.0.global_time as f64;
+
+    let velocity = match view_frame.body.map(|body| query_bodies.get(body)) {
+        None => None,
+        Some(Ok((Some(path), _))) => match path.past_light_cone_event(player_position, global_time) {
+            Sighting::At(_, time) => Some(path.velocity_at(time / SPEED_OF_LIGHT as f64)),
+            _ => None
+        },
+        Some(Ok((_, Some(AnalyticPath(trajectory))))) => trajectory.past_light_cone_time(player_position.0, global_time)
+            .map(|time| trajectory.velocity(time)),
+        // Its path file is still loading
+        Some(Ok((None, None))) => None,
+        // Gone with the level it was in
+        Some(Err(_)) => {
+            view_frame.body = None;
+            None
+        }
+    };
+
+    // Bodies that can't be seen yet (or any more) leave the player's frame in place
+    view_frame.velocity = velocity.unwrap_or(rendered_player.0.velocity);
+}
+
 fn reorient_angle_markers(
-    query_velocity: Query<&Velocity, With<Player>>,
+    view_frame: Res<ViewFrame>,
+    settings: Res<Settings>,
     mut angle_marker_transforms: Query<&mut Transform, With<AngleMarker>>
 ) {
-    let player_velocity = query_velocity.single();
-    let rotor = velocity_to_rotor(player_velocity);
+    let AngleMarkerSettings { count, orbit_radius, .. } = settings.angle_markers;
+    let rotor = velocity_to_rotor(&Velocity(view_frame.velocity));
 
     for (i, mut transform) in angle_marker_transforms.iter_mut().enumerate() {
         let mut vector = R120::new(1.0, 1);
-        vector[2] = (i as f32 * 2.0 * std::f32::consts::PI / (NUM_ANGLE_MARKERS as f32)).cos();
-        vector[3] = (i as f32 * 2.0 * std::f32::consts::PI / (NUM_ANGLE_MARKERS as f32)).sin();
+        vector[2] = (i as f32 * 2.0 * std::f32::consts::PI / (count as f32)).cos();
+        vector[3] = (i as f32 * 2.0 * std::f32::consts::PI / (count as f32)).sin();
 
         vector = rotor * (vector * rotor.Reverse());
 
@@ -31,17 +74,18 @@ fn reorient_angle_markers(
         vector[1] = 0.0;
         vector = vector.normalized();
 
-        transform.translation.x = ORBIT_RADIUS * vector[2];
-        transform.translation.y = ORBIT_RADIUS * vector[3];
+        transform.translation.x = orbit_radius * vector[2];
+        transform.translation.y = orbit_radius * vector[3];
     }
 }
 
 fn reorient_points(
-    query_player: Query<(&Position, &Velocity), With<Player>>,
+    rendered_player: Res<RenderedPlayer>,
+    view_frame: Res<ViewFrame>,
     mut points_transforms: Query<(&mut Transform, &Position), With<Point>>
 ) {
-    let (player_position, player_velocity) = query_player.single();
-    let rotor = velocity_to_rotor(player_velocity);
+    let player_position = Position(rendered_player.0.position);
+    let rotor = velocity_to_rotor(&Velocity(view_frame.velocity));
 
     for (mut transform, Position(pos)) in points_transforms.iter_mut() {
         let mut vector = R120::new(-((pos.x - player_position.0.x).powi(2) + (pos.y - player_position.0.y).powi(2)).powf(0.5), 1);
@@ -56,41 +100,111 @@ fn reorient_points(
 }
 
 fn reorient_paths(
-    query_player: Query<(&Position, &Velocity), With<Player>>,
-    mut points_transforms: Query<(&mut Transform, &Path), With<Path>>,
-    query_global_time: Res<GlobalTime>
+    rendered_player: Res<RenderedPlayer>,
+    view_frame: Res<ViewFrame>,
+    mut points_transforms: Query<(&mut Transform, &mut Visibility, &Path)>
 ) {
-    let (player_position, player_velocity) = query_player.single();
-    let rotor = velocity_to_rotor(player_velocity);
-    let global_time = query_global_time.0 * SPEED_OF_LIGHT;
+    let player_position = &Position(rendered_player.0.position);
+    let rotor = velocity_to_rotor(&Velocity(view_frame.velocity));
+
+    for (mut transform, mut visibility, path) in points_transforms.iter_mut() {
+        let point = match path.past_light_cone_event(player_position, rendered_player.0.global_time as f64) {
+            Sighting::At(point, _) => point,
+            // Kept around rather than despawned, rewinding or loading an earlier save can see it again
+            Sighting::BeforeStart | Sighting::AfterEnd => {
+                visibility.is_visible = false;
+                continue;
+            }
+        };
+
+        if !visibility.is_visible {
+            visibility.is_visible = true;
+        }
+
+        let point = reorient_light_cone_point(point, rotor);
+
+        transform.translation.x = point.x;
+        transform.translation.y = point.y;
+    }
+}
 
-    for (mut transform, path) in points_transforms.iter_mut() {
-        let bounds = path.get_bounds_at_time(player_position, query_global_time.0 as f64);
-        
-        let a: f64 = (bounds.1.0.x as f64 - bounds.0.0.x as f64).powi(2) + (bounds.1.0.y as f64 - bounds.0.0.y as f64).powi(2) + (bounds.1.1 - bounds.0.1).powi(2);
-        let b: f64 = 2.0 * (bounds.0.0.x as f64 * (bounds.1.0.x as f64 - bounds.0.0.x as f64) + bounds.0.0.y as f64 * (bounds.1.0.y as f64 - bounds.0.0.y as f64) - (global_time as f64 - bounds.0.1) * (bounds.1.1 - bounds.0.1));
-        let c: f64 = (bounds.0.0.x as f64).powi(2) + (bounds.0.0.y as f64).powi(2) + bounds.0.1.powi(2) - 2.0 * bounds.0.1 * global_time as f64 + (global_time as f64).powi(2);
-        let p: f64 = (-b - (b.powi(2) - 4.0 * a * c).abs().powf(0.5)) / (2.0 * a);
+fn reorient_analytic_paths(
+    rendered_player: Res<RenderedPlayer>,
+    view_frame: Res<ViewFrame>,
+    mut analytic_paths_transforms: Query<(&mut Transform, &mut Visibility, &AnalyticPath)>
+) {
+    let player_position = &Position(rendered_player.0.position);
+    let rotor = velocity_to_rotor(&Velocity(view_frame.velocity));
 
-        // Small errors resulting in p being slightly out of bounds caused visual glitches
-        // if p < 0.0 || p > 1.0 {
-        //     p = (-b + (b.powi(2) - 4.0 * a * c).abs().powf(0.5)) / (2.0 * a);
-        // }
+    for (mut transform, mut visibility, AnalyticPath(trajectory)) in analytic_paths_transforms.iter_mut() {
+        let Some(time) = trajectory.past_light_cone_time(player_position.0, rendered_player.0.global_time as f64) else {
+            visibility.is_visible = false;
+            continue;
+        };
 
-        let point: Vec2 = bounds.0.0 + p as f32 * (bounds.1.0 - bounds.0.0);
+        if !visibility.is_visible {
+            visibility.is_visible = true;
+        }
 
-        let mut vector = R120::new(-point.length(), 1);
-        vector[2] = point.x;
-        vector[3] = point.y;
+        let point = reorient_light_cone_point(trajectory.position(time) - player_position.0, rotor);
 
-        vector = rotor * (vector * rotor.Reverse());
+        transform.translation.x = point.x;
+        transform.translation.y = point.y;
+    }
+}
 
-        transform.translation.x = vector[2];
-        transform.translation.y = vector[3];
+// Colours each shaded path by the ratio of the frequency the player sees to the one it gives off,
+// using its velocity when the light left it and the velocity of the frame being viewed from
+fn doppler_shade_paths(
+    rendered_player: Res<RenderedPlayer>,
+    view_frame: Res<ViewFrame>,
+    settings: Res<Settings>,
+    query_paths: Query<(&Path, &DopplerShaded, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>
+) {
+    let player_position = &Position(rendered_player.0.position);
+    let player_velocity = Velocity(view_frame.velocity);
+
+    for (path, DopplerShaded(color), material_handle) in query_paths.iter() {
+        let Sighting::At(point, time) = path.past_light_cone_event(player_position, rendered_player.0.global_time as f64) else { continue };
+        let Some(material) = materials.get_mut(material_handle) else { continue };
+
+        // The direction the light travels in, from the path to the player
+        let direction = -point.normalize_or_zero();
+        let time = time / SPEED_OF_LIGHT as f64;
+        let source_velocity = path.velocity_at(time);
+
+        let seen = gamma(player_velocity.0) * (1.0 - player_velocity.0.dot(direction) / SPEED_OF_LIGHT);
+        let given_off = path.gamma_at(time) * (1.0 - source_velocity.dot(direction) / SPEED_OF_LIGHT);
+
+        material.color = doppler_shift(*color, seen / given_off, &settings.doppler);
     }
 }
 
-fn velocity_to_rotor(velocity: &Velocity) -> R120 {
+// Frequencies ratio > 1 is blue shifted (up the hue wheel), < 1 is red shifted
+fn doppler_shift(color: Color, frequency_ratio: f32, doppler: &DopplerSettings) -> Color {
+    let Color::Hsla { hue, saturation, lightness, alpha } = color.as_hsla() else { return color };
+
+    Color::hsla((hue + doppler.hue_shift * frequency_ratio.log2()).clamp(0.0, doppler.max_hue), saturation, lightness, alpha)
+}
+
+fn gamma(velocity: Vec2) -> f32 {
+    1.0 / (1.0 - (velocity.length() / SPEED_OF_LIGHT).powi(2)).max(f32::EPSILON).sqrt()
+}
+
+// Takes a point on the past light cone (relative to the player) into the player's rest frame
+fn reorient_light_cone_point(point: Vec2, rotor: R120) -> Vec2 {
+    let mut vector = R120::new(-point.length(), 1);
+    vector[2] = point.x;
+    vector[3] = point.y;
+
+    vector = rotor * (vector * rotor.Reverse());
+
+    Vec2::new(vector[2], vector[3])
+}
+
+// The rotor that boosts the rest frame into one moving at velocity
+pub fn velocity_to_rotor(velocity: &Velocity) -> R120 {
     let mut velocity_vector = R120::new(1.0, 1);
     velocity_vector[2] = velocity.0.x / SPEED_OF_LIGHT;
     velocity_vector[3] = velocity.0.y / SPEED_OF_LIGHT;
@@ -105,4 +219,25 @@ fn velocity_to_rotor(velocity: &Velocity) -> R120 {
     // println!("Bivector: {} \nRotor: {}\nVelocity vector: {}\nRv(~R): {}", product, rotor, velocity_vector, rotor * (velocity_vector * rotor.Reverse()));
 
     rotor
-}
\ No newline at end of file
+}
+
+// The rotor that turns +x to point along heading, in the rest frame
+pub fn heading_to_rotor(heading: f32) -> R120 {
+    let mut rotor = R120::new((heading / 2.0).cos(), 0);
+    rotor[6] = (heading / 2.0).sin();
+
+    rotor
+}
+
+// Which way a ship with this heading points as seen from the world frame, where its nose is
+// boosted along with the rest of it
+pub fn heading_direction(heading: f32, velocity: &Velocity) -> Vec2 {
+    let turn = heading_to_rotor(heading);
+    let boost = velocity_to_rotor(velocity);
+
+    let mut nose = R120::new(1.0, 2);
+    nose = turn * (nose * turn.Reverse());
+    nose = boost.Reverse() * (nose * boost);
+
+    Vec2::new(nose[2], nose[3]).normalize_or_zero()
+}
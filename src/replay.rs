@@ -0,0 +1,502 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, path::{Path as FilePath, PathBuf}};
+
+use super::*;
+
+// Records the player's input every physics step so that a run can be played back exactly, e.g.
+// to go with a bug report
+//
+//     cargo run -- --record saves/bug.replay.json
+//     cargo run -- --replay saves/bug.replay.json
+//
+// A recording is a save of the level as it was when it started (see src/save.rs), the settings
+// and the dust's seed, then the input of every step since along with the step the settings were
+// reloaded on, if they were. The physics doesn't depend on anything else, so feeding the inputs
+// back one step at a time retraces the run. Recording starts over whenever a level starts or a
+// save is loaded, and while a replay plays its settings win over any reloaded ones
+//
+// Controls:
+//   F6            write the recording so far
+//   F7            play the recording file, or stop playing and take over from there
+//
+// While playing:
+//   Left/Right    scrub back/forward REPLAY_SCRUB_TIME
+//
+// and it's paused and sped up like the game is, see src/time_control.rs
+
+const DEFAULT_REPLAY_FILE: &str = "saves/replay.replay.json";
+pub const REPLAY_VERSION: u64 = 1;
+
+// Seconds of the player's proper time
+const REPLAY_SCRUB_TIME: f32 = 5.0;
+
+const REPLAY_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const REPLAY_FONT_SIZE: f32 = 16.0;
+const REPLAY_MARGIN: f32 = 10.0;
+const REPLAY_BAR_LENGTH: usize = 40;
+
+const REPLAY_COLOR: Color = Color::rgb(220.0 / 256.0, 220.0 / 256.0, 235.0 / 256.0 );
+
+pub struct ReplayPlugin {
+    pub file: PathBuf,
+    pub play_at_start: bool
+}
+
+impl ReplayPlugin {
+    // Reads --record <file>, or --replay <file> to also start by playing it
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let mut plugin = ReplayPlugin { file: PathBuf::from(DEFAULT_REPLAY_FILE), play_at_start: false };
+        let mut args = args.skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--record" | "--replay" => match args.next() {
+                    Some(file) => {
+                        plugin.file = PathBuf::from(file);
+                        plugin.play_at_start |= arg == "--replay";
+                    },
+                    None => warn!("{} needs a replay file, using {:?}", arg, plugin.file)
+                },
+                _ => {}
+            }
+        }
+
+        plugin
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Recorder>()
+            .init_resource::<Replay>()
+            .add_system_set_to_stage(PhysicsStage::Step, SystemSet::new()
+                .with_run_criteria(PhysicsSteps)
+                .with_system(play_recorded_input
+                    .label(ReplaySystem::Play)
+                    .after(BindingsSystem::ReadInput)
+                    .before(PhysicsSystem::Move))
+                .with_system(record_input.after(ReplaySystem::Play)))
+            .insert_resource(ReplayFile(self.file.clone()))
+            .insert_resource(PendingReplay(self.play_at_start))
+            .add_startup_system(setup_replay_viewer)
+            // Between the level being spawned and the player being reset at the next physics step
+            .add_system_to_stage(CoreStage::PreUpdate, start_recording)
+            .add_system(write_recording)
+            .add_system(toggle_replay)
+            .add_system(control_replay.after(toggle_replay))
+            .add_system(draw_replay_viewer.after(control_replay));
+    }
+}
+
+#[derive(SystemLabel)]
+pub enum ReplaySystem {
+    // Replaces the player's input with the recorded one on every physics step while playing
+    Play
+}
+
+// ----------------------------------<< Types >>----------------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recording {
+    pub version: u64,
+    pub dust_seed: u64,
+    pub settings: Settings,
+    pub start: SaveGame,
+    // One for each run of steps with the same input, in order
+    pub inputs: Vec<RecordedInput>,
+    // In order, whenever the settings were reloaded while it was recorded
+    #[serde(default)]
+    pub settings_changes: Vec<SettingsChange>
+}
+
+// The settings the physics ran with from `step` on
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SettingsChange {
+    pub step: usize,
+    pub settings: Settings
+}
+
+// PlayerInput held for `steps` physics steps in a row
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RecordedInput {
+    pub steps: u32,
+    pub thrust: (f32, f32),
+    pub brake: bool,
+    // Recordings from before ships had a heading never steer
+    #[serde(default)]
+    pub forward: f32,
+    #[serde(default)]
+    pub aim: Option<f32>,
+    #[serde(default)]
+    pub turn: f32
+}
+
+impl RecordedInput {
+    fn new(steps: u32, input: PlayerInput) -> Self {
+        RecordedInput {
+            steps,
+            thrust: input.thrust.into(),
+            brake: input.brake,
+            forward: input.forward,
+            aim: input.aim,
+            turn: input.turn
+        }
+    }
+
+    fn input(&self) -> PlayerInput {
+        PlayerInput {
+            thrust: self.thrust.into(),
+            brake: self.brake,
+            forward: self.forward,
+            aim: self.aim,
+            turn: self.turn
+        }
+    }
+}
+
+impl Recording {
+    pub fn steps(&self) -> usize {
+        self.inputs.iter().map(|input| input.steps as usize).sum()
+    }
+
+    // The ones the physics ran with on `step`
+    fn settings_at(&self, step: usize) -> &Settings {
+        self.settings_changes.iter()
+            .rev()
+            .find(|change| change.step <= step)
+            .map_or(&self.settings, |change| &change.settings)
+    }
+
+    // Before the step's input is pushed
+    fn push_settings(&mut self, settings: &Settings) {
+        let latest = self.settings_changes.last().map_or(&self.settings, |change| &change.settings);
+
+        if latest != settings {
+            self.settings_changes.push(SettingsChange { step: self.steps(), settings: settings.clone() });
+        }
+    }
+
+    fn push(&mut self, input: PlayerInput) {
+        match self.inputs.last_mut() {
+            Some(last) if last.input() == input => last.steps += 1,
+            _ => self.inputs.push(RecordedInput::new(1, input))
+        }
+    }
+
+    // The input of every step, one after the other
+    fn unpack(&self) -> Vec<PlayerInput> {
+        self.inputs.iter()
+            .flat_map(|input| std::iter::repeat_n(input.input(), input.steps as usize))
+            .collect()
+    }
+
+    // Only the first `steps` steps
+    fn truncated(&self, steps: usize) -> Self {
+        let mut recording = Recording { inputs: Vec::new(), ..self.clone() };
+        recording.settings_changes.retain(|change| change.step < steps);
+
+        for input in self.unpack().into_iter().take(steps) {
+            recording.push(input);
+        }
+
+        recording
+    }
+
+    pub fn write(&self, file: &FilePath) -> Result<(), String> {
+        if let Some(folder) = file.parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            std::fs::create_dir_all(folder).map_err(|err| err.to_string())?;
+        }
+
+        let json = serde_json::to_string(self).map_err(|err| err.to_string())?;
+        std::fs::write(file, json).map_err(|err| err.to_string())
+    }
+
+    // The inputs only mean anything to the physics they were recorded with, so unlike saves,
+    // other versions aren't played
+    pub fn read(file: &FilePath) -> Result<Self, String> {
+        let text = std::fs::read_to_string(file).map_err(|err| err.to_string())?;
+        let mut json: serde_json::Value = serde_json::from_str(&text).map_err(|err| err.to_string())?;
+
+        let version = json.get("version").and_then(|version| version.as_u64())
+            .ok_or_else(|| "it has no version".to_string())?;
+
+        if version != REPLAY_VERSION {
+            return Err(format!("it's version {} but this game only plays version {}", version, REPLAY_VERSION));
+        }
+
+        // The save it starts from is brought up to date like any other
+        let start = SaveGame::migrate(json["start"].take())?;
+        json["start"] = serde_json::to_value(start).map_err(|err| err.to_string())?;
+
+        // Recorded before the settings were checked, the run can't be played as it was anyway
+        let mut recording: Recording = serde_json::from_value(json).map_err(|err| err.to_string())?;
+        recording.settings = recording.settings.validated();
+
+        for change in recording.settings_changes.iter_mut() {
+            change.settings = change.settings.clone().validated();
+        }
+
+        Ok(recording)
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct ReplayFile(pub PathBuf);
+
+// Plays the replay file on the next frame
+#[derive(Resource)]
+pub struct PendingReplay(pub bool);
+
+// The run since the level started, None until one has
+#[derive(Resource, Default)]
+pub struct Recorder(pub Option<Recording>);
+
+#[derive(Resource, Default)]
+pub struct Replay {
+    // The recording being played back, None while the player is in control
+    pub recording: Option<Recording>,
+    // Steps played so far
+    pub step: usize,
+    // The recording's input for every step
+    inputs: Vec<PlayerInput>,
+    // Set while the replay is putting its start back, so that it isn't taken for the player
+    // choosing another level
+    restoring: bool
+}
+
+impl Replay {
+    pub fn is_playing(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    // Goes back to the start of the recording, and has the physics catch up to `step` on the
+    // next frame
+    fn restore(&mut self, step: usize, physics_clock: &mut PhysicsClock, level_spawner: &mut LevelSpawner, level_select: &mut LevelSelect) {
+        let Some(recording) = &self.recording else { return };
+
+        recording.start.restore(level_spawner, level_select);
+        self.step = 0;
+        self.restoring = true;
+        physics_clock.extra_steps = step.min(self.inputs.len());
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct ReplayViewerText;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// Runs in the physics step, standing in for the keyboard while a replay is playing
+fn play_recorded_input(mut replay: ResMut<Replay>, mut player_input: ResMut<PlayerInput>, mut settings: ResMut<Settings>) {
+    let Some(recording) = &replay.recording else { return };
+
+    let recorded = recording.settings_at(replay.step);
+    if *settings != *recorded {
+        *settings = recorded.clone();
+    }
+
+    if let Some(input) = replay.inputs.get(replay.step).copied() {
+        *player_input = input;
+        replay.step += 1;
+    }
+}
+
+// Runs in the physics step, after the input has been read
+fn record_input(replay: Res<Replay>, player_input: Res<PlayerInput>, settings: Res<Settings>, mut recorder: ResMut<Recorder>) {
+    if replay.is_playing() {
+        return;
+    }
+
+    if let Some(recording) = recorder.0.as_mut() {
+        recording.push_settings(&settings);
+        recording.push(*player_input);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_recording(
+    player_reset: Res<PlayerReset>,
+    current_level: Option<Res<CurrentLevel>>,
+    asset_server: Res<AssetServer>,
+    objectives: Res<Objectives>,
+    settings: Res<Settings>,
+    dust_seed: Res<DustSeed>,
+    query_bodies: Query<&LevelBody>,
+    mut replay: ResMut<Replay>,
+    mut physics_clock: ResMut<PhysicsClock>,
+    mut recorder: ResMut<Recorder>
+) {
+    let (Some(state), Some(current_level)) = (player_reset.state, current_level) else { return };
+
+    if replay.restoring {
+        replay.restoring = false;
+        return;
+    }
+
+    if replay.is_playing() {
+        info!("Stopped the replay since another level started");
+        replay.recording = None;
+        physics_clock.step_limit = None;
+    }
+
+    recorder.0 = Some(Recording {
+        version: REPLAY_VERSION,
+        dust_seed: dust_seed.0,
+        settings: settings.clone(),
+        start: SaveGame::new(
+            current_level.file(&asset_server),
+            &current_level.asset,
+            query_bodies.iter().cloned().collect(),
+            state,
+            &player_reset.history,
+            &objectives
+        ),
+        inputs: Vec::new(),
+        settings_changes: Vec::new()
+    });
+}
+
+fn write_recording(keyboard_input: Res<Input<KeyCode>>, replay_file: Res<ReplayFile>, recorder: Res<Recorder>) {
+    if !keyboard_input.just_pressed(KeyCode::F6) {
+        return;
+    }
+
+    let Some(recording) = &recorder.0 else {
+        warn!("There's nothing recorded until a level is being played");
+        return;
+    };
+
+    match recording.write(&replay_file.0) {
+        Ok(()) => info!("Wrote {} steps of {} to {:?}", recording.steps(), recording.start.level.name, replay_file.0),
+        Err(err) => error!("Couldn't write the recording to {:?}: {}", replay_file.0, err)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn toggle_replay(
+    keyboard_input: Res<Input<KeyCode>>,
+    replay_file: Res<ReplayFile>,
+    mut pending_replay: ResMut<PendingReplay>,
+    mut replay: ResMut<Replay>,
+    mut recorder: ResMut<Recorder>,
+    mut physics_clock: ResMut<PhysicsClock>,
+    mut settings: ResMut<Settings>,
+    mut dust_seed: ResMut<DustSeed>,
+    mut level_select: ResMut<LevelSelect>,
+    mut level_spawner: LevelSpawner
+) {
+    if !keyboard_input.just_pressed(KeyCode::F7) && !pending_replay.0 {
+        return;
+    }
+
+    pending_replay.0 = false;
+
+    // The player takes over, and what was played so far is kept as the start of their recording
+    if let Some(recording) = replay.recording.take() {
+        info!("Took over from the replay after {} steps", replay.step);
+        recorder.0 = Some(recording.truncated(replay.step));
+        physics_clock.step_limit = None;
+        return;
+    }
+
+    let recording = match Recording::read(&replay_file.0) {
+        Ok(recording) => recording,
+        Err(err) => {
+            error!("Couldn't play {:?}: {}", replay_file.0, err);
+            return;
+        }
+    };
+
+    if *settings != recording.settings {
+        info!("Playing the replay with the settings it was recorded with");
+        *settings = recording.settings.clone();
+    }
+
+    dust_seed.0 = recording.dust_seed;
+
+    info!("Playing {} steps of {} from {:?}", recording.steps(), recording.start.level.name, replay_file.0);
+    *replay = Replay { inputs: recording.unpack(), recording: Some(recording), ..Replay::default() };
+    replay.restore(0, &mut physics_clock, &mut level_spawner, &mut level_select);
+}
+
+fn control_replay(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut replay: ResMut<Replay>,
+    mut physics_clock: ResMut<PhysicsClock>,
+    mut level_select: ResMut<LevelSelect>,
+    mut level_spawner: LevelSpawner
+) {
+    if !replay.is_playing() {
+        return;
+    }
+
+    let scrub_steps = (REPLAY_SCRUB_TIME / TIMESTEP).round() as usize;
+
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        // The physics can only go forwards, so going back replays everything up to there again
+        let step = replay.step.saturating_sub(scrub_steps);
+        replay.restore(step, &mut physics_clock, &mut level_spawner, &mut level_select);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        physics_clock.extra_steps += scrub_steps;
+    }
+
+    physics_clock.step_limit = Some(replay.inputs.len().saturating_sub(replay.step));
+}
+
+fn setup_replay_viewer(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((TextBundle::from_section("", TextStyle {
+        font: asset_server.load(REPLAY_FONT),
+        font_size: REPLAY_FONT_SIZE,
+        color: REPLAY_COLOR
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            bottom: Val::Px(REPLAY_MARGIN),
+            left: Val::Px(REPLAY_MARGIN),
+            ..default()
+        },
+        ..default()
+    }), ReplayViewerText));
+}
+
+fn draw_replay_viewer(
+    replay: Res<Replay>,
+    physics_clock: Res<PhysicsClock>,
+    mut query_text: Query<(&mut Text, &mut Visibility), With<ReplayViewerText>>
+) {
+    let (mut text, mut visibility) = query_text.single_mut();
+
+    if visibility.is_visible != replay.is_playing() {
+        visibility.is_visible = replay.is_playing();
+    }
+
+    if !replay.is_playing() {
+        return;
+    }
+
+    let steps = replay.inputs.len().max(1);
+    let filled = replay.step * REPLAY_BAR_LENGTH / steps;
+    let status = if replay.step >= replay.inputs.len() { "finished" } else if physics_clock.paused { "paused" } else { "" };
+
+    let viewer = &mut text.sections[0].value;
+    viewer.clear();
+    let _ = writeln!(
+        viewer,
+        "Replay [{}{}] {:>8.2}s / {:.2}s  x{}  {}",
+        "=".repeat(filled),
+        "-".repeat(REPLAY_BAR_LENGTH - filled),
+        replay.step as f32 * TIMESTEP,
+        replay.inputs.len() as f32 * TIMESTEP,
+        physics_clock.speed,
+        status
+    );
+    let _ = writeln!(viewer, "Left/Right scrub, F7 take over");
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+
+use super::*;
+
+// Looks back at the scene from an earlier event on the player's worldline. Paths are defined for
+// all coordinate time, so drawing from a past event shows everything as the player saw it then,
+// light cone and all. Only the view goes back, the simulation carries on underneath
+//
+// Controls:
+//   R             start/stop rewinding
+//   Comma/Period  while rewinding, scrub back/forward through coordinate time
+//   Shift         scrub faster
+
+// Seconds of coordinate time per second held
+const REWIND_SCRUB_RATE: f32 = 5.0;
+const REWIND_FAST_SCRUB_RATE: f32 = 50.0;
+
+const REWIND_FONT: &str = "fonts/DejaVuSansMono.ttf";
+const REWIND_FONT_SIZE: f32 = 16.0;
+const REWIND_MARGIN: f32 = 10.0;
+
+const REWIND_COLOR: Color = Color::rgb(255.0 / 256.0, 220.0 / 256.0, 120.0 / 256.0 );
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Rewind>()
+            .add_startup_system(setup_rewind)
+            .add_system(control_rewind)
+            .add_system(draw_rewind.after(control_rewind))
+            .add_system_to_stage(PhysicsStage::Interpolate, rewind_rendered_player.after(PhysicsSystem::Interpolate));
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource, Default)]
+pub struct Rewind {
+    // The coordinate time the scene is being seen from, None while watching the present
+    pub time: Option<f32>
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+#[derive(Component)]
+struct RewindText;
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn setup_rewind(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((TextBundle::from_section("", TextStyle {
+        font: asset_server.load(REWIND_FONT),
+        font_size: REWIND_FONT_SIZE,
+        color: REWIND_COLOR
+    }).with_style(Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            top: Val::Px(REWIND_MARGIN),
+            left: Val::Percent(40.0),
+            ..default()
+        },
+        ..default()
+    }), RewindText));
+}
+
+fn control_rewind(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    worldline: Res<Worldline>,
+    mut rewind: ResMut<Rewind>
+) {
+    let (Some(first), Some(last)) = (worldline.0.first(), worldline.0.last()) else { return };
+
+    if keyboard_input.just_pressed(KeyCode::R) {
+        rewind.time = match rewind.time {
+            Some(_) => None,
+            None => Some(last.global_time)
+        };
+    }
+
+    let Some(rewind_time) = rewind.time else { return };
+
+    let rate = if keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]) { REWIND_FAST_SCRUB_RATE } else { REWIND_SCRUB_RATE };
+    let direction = (keyboard_input.pressed(KeyCode::Period) as i32 - keyboard_input.pressed(KeyCode::Comma) as i32) as f32;
+
+    // Also keeps it inside the worldline when a level starts over under it
+    let scrubbed = (rewind_time + direction * rate * time.delta_seconds()).clamp(first.global_time, last.global_time);
+
+    if scrubbed != rewind_time {
+        rewind.time = Some(scrubbed);
+    }
+}
+
+// Draws from the past event rather than where the player is now, which everything that only
+// draws picks up through RenderedPlayer
+fn rewind_rendered_player(
+    rewind: Res<Rewind>,
+    worldline: Res<Worldline>,
+    mut rendered_player: ResMut<RenderedPlayer>
+) {
+    let Some(state) = rewind.time.and_then(|time| worldline.at(time)) else { return };
+
+    rendered_player.0 = state;
+}
+
+fn draw_rewind(
+    rewind: Res<Rewind>,
+    worldline: Res<Worldline>,
+    mut query_text: Query<(&mut Text, &mut Visibility), With<RewindText>>
+) {
+    let (mut text, mut visibility) = query_text.single_mut();
+
+    if visibility.is_visible != rewind.time.is_some() {
+        visibility.is_visible = rewind.time.is_some();
+    }
+
+    let (Some(time), Some(last)) = (rewind.time, worldline.0.last()) else { return };
+
+    text.sections[0].value = format!(
+        "Rewound {:.2}s, seeing coordinate time {:.2}s\nComma/Period scrub, Shift faster, R back to now",
+        last.global_time - time,
+        time
+    );
+}
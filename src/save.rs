@@ -0,0 +1,349 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path as FilePath, PathBuf};
+
+use super::*;
+
+// Saves the whole simulation to a JSON file and puts it back, e.g.
+//
+//     cargo run -- --save-file saves/slot1.save.json
+//     cargo run -- --load saves/slot1.save.json
+//
+// A save holds the level as it is being played, with only the bodies still around, the player's
+// state and clocks, the worldline it has drawn so far and how the objectives are going. Paths are
+// functions of coordinate time, so putting back the bodies and the clocks puts them where they were
+//
+// Every save has a version. Older ones are upgraded one version at a time by MIGRATIONS before
+// they're read. Newer ones aren't read at all, this game can't know what their fields mean
+//
+// Controls:
+//   F5            save
+//   F9            load
+
+const DEFAULT_SAVE_FILE: &str = "saves/quicksave.save.json";
+pub const SAVE_VERSION: u64 = 1;
+
+// MIGRATIONS[i] turns a version i + 1 save into a version i + 2 one, add one whenever
+// SAVE_VERSION goes up
+const MIGRATIONS: &[Migration] = &[];
+
+type Migration = fn(&mut serde_json::Value);
+
+const _: () = assert!(MIGRATIONS.len() as u64 + 1 == SAVE_VERSION, "Every version but the first needs a migration");
+
+pub struct SavePlugin {
+    pub file: PathBuf,
+    pub load_at_start: bool
+}
+
+impl SavePlugin {
+    // Reads --save-file <file>, or --load <file> to also start from that save
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let mut plugin = SavePlugin { file: PathBuf::from(DEFAULT_SAVE_FILE), load_at_start: false };
+        let mut args = args.skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--save-file" | "--load" => match args.next() {
+                    Some(file) => {
+                        plugin.file = PathBuf::from(file);
+                        plugin.load_at_start |= arg == "--load";
+                    },
+                    None => warn!("{} needs a save file, using {:?}", arg, plugin.file)
+                },
+                _ => {}
+            }
+        }
+
+        plugin
+    }
+}
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(SaveFile(self.file.clone()))
+            .insert_resource(PendingLoad(self.load_at_start))
+            .add_system(save_game)
+            .add_system(load_game);
+    }
+}
+
+// ----------------------------------<< Types >>----------------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveGame {
+    pub version: u64,
+    // Relative to the assets folder, so that the level restarts from its file when that changes
+    #[serde(default)]
+    pub level_file: Option<String>,
+    pub level: LevelAsset,
+    pub player: SavedPlayerState,
+    #[serde(default)]
+    pub worldline: Vec<SavedPlayerState>,
+    // One for each of the level's objectives, in order
+    #[serde(default)]
+    pub objectives: Vec<ObjectiveState>
+}
+
+// PlayerState as it's written to a save, kept apart so that the file doesn't change with it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct SavedPlayerState {
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    pub global_time: f32,
+    pub local_time: f32,
+    // Saves from before ships had a heading point along +x
+    #[serde(default)]
+    pub heading: f32
+}
+
+impl From<PlayerState> for SavedPlayerState {
+    fn from(state: PlayerState) -> Self {
+        SavedPlayerState {
+            position: state.position.into(),
+            velocity: state.velocity.into(),
+            global_time: state.global_time,
+            local_time: state.local_time,
+            heading: state.heading
+        }
+    }
+}
+
+impl From<SavedPlayerState> for PlayerState {
+    fn from(state: SavedPlayerState) -> Self {
+        PlayerState {
+            position: state.position.into(),
+            velocity: state.velocity.into(),
+            global_time: state.global_time,
+            local_time: state.local_time,
+            heading: state.heading
+        }
+    }
+}
+
+impl SaveGame {
+    // The level as it's being played, `bodies` being the ones still around
+    pub fn new(
+        level_file: Option<String>,
+        level: &LevelAsset,
+        bodies: Vec<LevelBody>,
+        player: PlayerState,
+        worldline: &[PlayerState],
+        objectives: &Objectives
+    ) -> Self {
+        SaveGame {
+            version: SAVE_VERSION,
+            level_file,
+            level: LevelAsset { bodies, ..level.clone() },
+            player: player.into(),
+            worldline: worldline.iter().map(|state| (*state).into()).collect(),
+            objectives: objectives.objectives.iter().map(|(_, state)| *state).collect()
+        }
+    }
+
+    // Plays the level from where the save left it, starting on the next physics step
+    pub fn restore(&self, level_spawner: &mut LevelSpawner, level_select: &mut LevelSelect) {
+        let handle = self.level_file.as_ref().map_or_else(Handle::default, |file| level_spawner.asset_server.load(file.as_str()));
+        level_spawner.spawn(handle, &self.level);
+
+        let mut objectives = Objectives::new(self.level.objectives.clone(), self.level.guided);
+
+        for ((_, state), saved) in objectives.objectives.iter_mut().zip(self.objectives.iter()) {
+            *state = *saved;
+        }
+
+        // After the spawner's, so these replace the level's starting ones
+        level_spawner.player_reset.state = Some(self.player.into());
+        level_spawner.player_reset.history = self.worldline.iter().map(|state| (*state).into()).collect();
+        level_spawner.commands.insert_resource(objectives);
+
+        level_select.open = false;
+        level_select.pending = None;
+    }
+
+    pub fn write(&self, file: &FilePath) -> Result<(), String> {
+        if let Some(folder) = file.parent().filter(|folder| !folder.as_os_str().is_empty()) {
+            std::fs::create_dir_all(folder).map_err(|err| err.to_string())?;
+        }
+
+        let json = serde_json::to_string(self).map_err(|err| err.to_string())?;
+        std::fs::write(file, json).map_err(|err| err.to_string())
+    }
+
+    pub fn read(file: &FilePath) -> Result<Self, String> {
+        let text = std::fs::read_to_string(file).map_err(|err| err.to_string())?;
+        let json = serde_json::from_str(&text).map_err(|err| err.to_string())?;
+
+        SaveGame::migrate(json)
+    }
+
+    // Brings a save of any version up to SAVE_VERSION
+    pub fn migrate(json: serde_json::Value) -> Result<Self, String> {
+        SaveGame::migrate_with(json, MIGRATIONS)
+    }
+
+    // Up to the version after the last of `migrations`, which MIGRATIONS and SAVE_VERSION always
+    // agree on outside of tests
+    fn migrate_with(mut json: serde_json::Value, migrations: &[Migration]) -> Result<Self, String> {
+        let latest = migrations.len() as u64 + 1;
+        let version = json.get("version").and_then(|version| version.as_u64())
+            .ok_or_else(|| "it has no version".to_string())?;
+
+        if version == 0 {
+            return Err("version 0 isn't a save version".to_string());
+        }
+
+        if version > latest {
+            return Err(format!("it's version {} but this game only reads up to {}", version, latest));
+        }
+
+        for (i, migration) in migrations.iter().enumerate().skip(version as usize - 1) {
+            migration(&mut json);
+            info!("Upgraded the save from version {} to {}", i + 1, i + 2);
+        }
+
+        json["version"] = latest.into();
+        serde_json::from_value(json).map_err(|err| err.to_string())
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct SaveFile(pub PathBuf);
+
+// Loads the save file on the next frame
+#[derive(Resource)]
+pub struct PendingLoad(pub bool);
+
+// ----------------------------------<< Systems >>----------------------------------
+
+#[allow(clippy::too_many_arguments)]
+fn save_game(
+    keyboard_input: Res<Input<KeyCode>>,
+    save_file: Res<SaveFile>,
+    current_level: Option<Res<CurrentLevel>>,
+    asset_server: Res<AssetServer>,
+    objectives: Res<Objectives>,
+    worldline: Res<Worldline>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    query_player: Query<(&Position, &Velocity, &Heading), With<Player>>,
+    query_bodies: Query<&LevelBody>
+) {
+    if !keyboard_input.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    let Some(current_level) = current_level else {
+        warn!("There's nothing to save until a level is being played");
+        return;
+    };
+
+    let (Position(position), Velocity(velocity), Heading(heading)) = query_player.single();
+    let player = PlayerState { position: *position, velocity: *velocity, global_time: global_time.0, local_time: local_time.0, heading: *heading };
+
+    let save = SaveGame::new(
+        current_level.file(&asset_server),
+        &current_level.asset,
+        query_bodies.iter().cloned().collect(),
+        player,
+        &worldline.0,
+        &objectives
+    );
+
+    match save.write(&save_file.0) {
+        Ok(()) => info!("Saved {} at coordinate time {:.2}s to {:?}", save.level.name, player.global_time, save_file.0),
+        Err(err) => error!("Couldn't save to {:?}: {}", save_file.0, err)
+    }
+}
+
+fn load_game(
+    keyboard_input: Res<Input<KeyCode>>,
+    save_file: Res<SaveFile>,
+    mut pending_load: ResMut<PendingLoad>,
+    mut level_select: ResMut<LevelSelect>,
+    mut level_spawner: LevelSpawner
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) && !pending_load.0 {
+        return;
+    }
+
+    pending_load.0 = false;
+
+    let save = match SaveGame::read(&save_file.0) {
+        Ok(save) => save,
+        Err(err) => {
+            error!("Couldn't load {:?}: {}", save_file.0, err);
+            return;
+        }
+    };
+
+    save.restore(&mut level_spawner, &mut level_select);
+    info!("Loaded {} at coordinate time {:.2}s from {:?}", save.level.name, save.player.global_time, save_file.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL_FILE: &str = "levels/03_twin_paradox.level.ron";
+
+    // Halfway through the twin paradox, a little way out with the first objective met
+    fn twin_paradox_save() -> SaveGame {
+        let text = std::fs::read_to_string(std::path::Path::new("assets").join(LEVEL_FILE)).unwrap();
+        let level: LevelAsset = ron::from_str(&text).unwrap();
+
+        let worldline: Vec<PlayerState> = (0..=120).map(|step| {
+            let time = step as f32 * TIMESTEP;
+            PlayerState { position: Vec2::new(1000.0 * time, 0.0), velocity: Vec2::new(1000.0, 0.0), global_time: time, local_time: time * 0.97, heading: 0.5 }
+        }).collect();
+
+        let mut objectives = Objectives::new(level.objectives.clone(), level.guided);
+        objectives.objectives[0].1 = ObjectiveState::Complete(0.97);
+
+        SaveGame::new(Some(LEVEL_FILE.to_string()), &level, level.bodies.clone(), *worldline.last().unwrap(), &worldline, &objectives)
+    }
+
+    #[test]
+    fn save_then_load_gives_the_same_save() {
+        let save = twin_paradox_save();
+        let file = std::env::temp_dir().join("spacetime_game_round_trip.save.json");
+
+        save.write(&file).unwrap();
+        let loaded = SaveGame::read(&file);
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(serde_json::to_value(&save).unwrap(), serde_json::to_value(loaded.unwrap()).unwrap());
+    }
+
+    #[test]
+    fn older_saves_are_migrated() {
+        // A made up version 1 that called the player the ship, upgraded by a made up version 2
+        fn rename_ship(json: &mut serde_json::Value) {
+            json["player"] = json["ship"].take();
+        }
+
+        let save = serde_json::to_value(twin_paradox_save()).unwrap();
+
+        let mut old = save.clone();
+        old["version"] = 1.into();
+        old["ship"] = old["player"].take();
+
+        let migrated = SaveGame::migrate_with(old, &[rename_ship]).unwrap();
+        assert_eq!(migrated.version, 2);
+
+        let mut expected = save;
+        expected["version"] = 2.into();
+        assert_eq!(serde_json::to_value(migrated).unwrap(), expected);
+    }
+
+    #[test]
+    fn newer_saves_are_rejected() {
+        let mut newer = serde_json::to_value(twin_paradox_save()).unwrap();
+        newer["version"] = (SAVE_VERSION + 1).into();
+
+        assert!(SaveGame::migrate(newer).is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, FileAssetIo, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid
+};
+use serde::{Deserialize, Serialize};
+
+use crate::SPEED_OF_LIGHT;
+
+// Tuning values for the physics and visuals, read from a RON file in assets that is reloaded
+// while the game runs. Anything missing from the file keeps its default. Values can also be
+// overridden from the command line, and the overrides stick through reloads, e.g.
+//
+//     cargo run -- --config game.settings.ron --set player.max_speed=1500 --set dusties.color=[1,0,0]
+//
+// Colours are (red, green, blue) from 0 to 1. The player's top speed has to stay below light
+// speed, faster ones are brought down to PLAYER_MAX_SPEED with a warning
+
+const DEFAULT_SETTINGS_FILE: &str = "game.settings.ron";
+
+// The fastest the player can be allowed to go. At light speed the Lorentz factor is infinite, and
+// close to it f32 can't tell the speeds apart
+pub const PLAYER_MAX_SPEED: f32 = 0.99 * SPEED_OF_LIGHT;
+
+pub struct SettingsPlugin {
+    // Relative to the assets folder
+    pub file: String,
+    // (dotted.key, value) pairs, values are parsed as JSON and otherwise taken as strings
+    pub overrides: Vec<(String, String)>,
+    pub live_reload: bool
+}
+
+impl SettingsPlugin {
+    // Reads --config <file> and any number of --set <key>=<value>
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let mut plugin = SettingsPlugin { file: DEFAULT_SETTINGS_FILE.to_string(), overrides: Vec::new(), live_reload: true };
+        let mut args = args.skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => match args.next() {
+                    Some(file) => plugin.file = file,
+                    None => warn!("--config needs a settings file in assets, using {}", plugin.file)
+                },
+                "--set" => match args.next().as_deref().and_then(|set| set.split_once('=')) {
+                    Some((key, value)) => plugin.overrides.push((key.trim().to_string(), value.trim().to_string())),
+                    None => warn!("--set needs a key=value pair")
+                },
+                _ => {}
+            }
+        }
+
+        plugin
+    }
+}
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        // Read straight away rather than through the AssetServer so that startup systems, and
+        // headless runs which don't have one, see the file's values
+        let path = FileAssetIo::get_base_path().join("assets").join(&self.file);
+
+        let settings = match std::fs::read_to_string(&path) {
+            Ok(text) => match ron::from_str::<Settings>(&text) {
+                Ok(settings) => settings,
+                Err(err) => {
+                    error!("Couldn't parse settings file {:?}, using the defaults: {}", path, err);
+                    Settings::default()
+                }
+            },
+            Err(err) => {
+                warn!("Couldn't read settings file {:?}, using the defaults: {}", path, err);
+                Settings::default()
+            }
+        };
+
+        app
+            .insert_resource(settings.with_overrides(&self.overrides).validated())
+            .insert_resource(SettingsOverrides(self.overrides.clone()));
+
+        if self.live_reload {
+            let file = self.file.clone();
+
+            app
+                .add_asset::<Settings>()
+                .init_asset_loader::<SettingsLoader>()
+                .add_startup_system(move |mut commands: Commands, asset_server: Res<AssetServer>| {
+                    commands.insert_resource(SettingsHandle(asset_server.load(file.as_str())));
+                })
+                .add_system(reload_settings);
+        }
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource, Serialize, Deserialize, TypeUuid, Debug, Clone, PartialEq)]
+#[uuid = "0c1e4a57-92f3-4b8e-a6d1-7f35c2e9b804"]
+#[serde(default)]
+pub struct Settings {
+    pub background_color: (f32, f32, f32),
+    pub player: PlayerSettings,
+    pub bounds: BoundsSettings,
+    pub angle_markers: AngleMarkerSettings,
+    pub dusties: DustSettings,
+    pub clock_dial: ClockDialSettings,
+    pub render_depths: RenderDepthSettings,
+    pub doppler: DopplerSettings
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PlayerSettings {
+    pub size: f32,
+    pub color: (f32, f32, f32),
+    pub max_speed: f32,
+    pub acceleration_x: f32,
+    pub acceleration_y: f32,
+    // Fractions of the velocity lost per step, braking while holding space and friction while
+    // not accelerating
+    pub braking: f32,
+    pub friction: f32,
+    // Radians per second while turning with the keys
+    pub turn_rate: f32
+}
+
+// The player gets pushed back inside these
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct BoundsSettings {
+    pub left: f32,
+    pub right: f32,
+    pub lower: f32,
+    pub upper: f32
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AngleMarkerSettings {
+    pub count: usize,
+    pub size: f32,
+    pub color: (f32, f32, f32),
+    pub orbit_radius: f32
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DustSettings {
+    pub count: usize,
+    pub min_size: f32,
+    pub max_size: f32,
+    pub color: (f32, f32, f32)
+}
+
+// Around the second/minute/day hands
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ClockDialSettings {
+    pub radius: f32,
+    pub tick_size: f32,
+    pub color: (f32, f32, f32)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct RenderDepthSettings {
+    pub point: f32,
+    pub path: f32,
+    pub player: f32
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct DopplerSettings {
+    // Degrees of hue per doubling of the seen frequency
+    pub hue_shift: f32,
+    // Violet, hues wrap back around to red after this
+    pub max_hue: f32
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            background_color: (20.0 / 255.0, 20.0 / 255.0, 40.0 / 255.0),
+            player: PlayerSettings::default(),
+            bounds: BoundsSettings::default(),
+            angle_markers: AngleMarkerSettings::default(),
+            dusties: DustSettings::default(),
+            clock_dial: ClockDialSettings::default(),
+            render_depths: RenderDepthSettings::default(),
+            doppler: DopplerSettings::default()
+        }
+    }
+}
+
+impl Default for PlayerSettings {
+    fn default() -> Self {
+        PlayerSettings {
+            size: 30.0,
+            color: (255.0 / 256.0, 195.0 / 256.0, 0.0 / 256.0),
+            max_speed: 2000.0,
+            acceleration_x: 2000.0,
+            acceleration_y: 2000.0,
+            braking: 0.05,
+            friction: 0.005,
+            turn_rate: 3.0
+        }
+    }
+}
+
+impl Default for BoundsSettings {
+    fn default() -> Self {
+        BoundsSettings { left: -20000.0, right: 20000.0, lower: -20000.0, upper: 20000.0 }
+    }
+}
+
+impl Default for AngleMarkerSettings {
+    fn default() -> Self {
+        AngleMarkerSettings {
+            count: 0,
+            size: 10.0,
+            color: (255.0 / 256.0, 87.0 / 256.0, 51.0 / 256.0),
+            orbit_radius: 100.0
+        }
+    }
+}
+
+impl Default for DustSettings {
+    fn default() -> Self {
+        DustSettings {
+            count: 200,
+            min_size: 1.0,
+            max_size: 4.5,
+            color: (80.0 / 256.0, 80.0 / 256.0, 100.0 / 256.0)
+        }
+    }
+}
+
+impl Default for ClockDialSettings {
+    fn default() -> Self {
+        ClockDialSettings {
+            radius: 120.0,
+            tick_size: 4.0,
+            color: (170.0 / 256.0, 170.0 / 256.0, 190.0 / 256.0)
+        }
+    }
+}
+
+impl Default for RenderDepthSettings {
+    fn default() -> Self {
+        RenderDepthSettings { point: 2.0, path: 4.0, player: 6.0 }
+    }
+}
+
+impl Default for DopplerSettings {
+    fn default() -> Self {
+        DopplerSettings { hue_shift: 120.0, max_hue: 270.0 }
+    }
+}
+
+impl Settings {
+    // Sets each dotted key, e.g. player.max_speed, by going through JSON so that any field can be
+    // reached without listing them all. Bad overrides are skipped with a warning
+    pub fn with_overrides(self, overrides: &[(String, String)]) -> Self {
+        let mut settings = self;
+
+        for (key, value) in overrides {
+            let result = serde_json::to_value(&settings).map_err(|err| err.to_string()).and_then(|mut json| {
+                let field = key.split('.').try_fold(&mut json, |json, name| json.get_mut(name))
+                    .ok_or_else(|| "there's no such setting".to_string())?;
+
+                *field = serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.clone()));
+                serde_json::from_value(json).map_err(|err| err.to_string())
+            });
+
+            match result {
+                Ok(overridden) => settings = overridden,
+                Err(err) => warn!("Ignoring --set {}={}: {}", key, value, err)
+            }
+        }
+
+        settings
+    }
+
+    // Brings values the physics can't run with back into range, with a warning
+    pub fn validated(mut self) -> Self {
+        let max_speed = self.player.max_speed;
+
+        if !(0.0..=PLAYER_MAX_SPEED).contains(&max_speed) {
+            let clamped = if max_speed.is_nan() { PlayerSettings::default().max_speed } else { max_speed.clamp(0.0, PLAYER_MAX_SPEED) };
+            warn!("player.max_speed {} isn't from 0 to {} (just below light speed), using {}", max_speed, PLAYER_MAX_SPEED, clamped);
+            self.player.max_speed = clamped;
+        }
+
+        self
+    }
+}
+
+pub fn rgb((red, green, blue): (f32, f32, f32)) -> Color {
+    Color::rgb(red, green, blue)
+}
+
+#[derive(Resource)]
+struct SettingsOverrides(Vec<(String, String)>);
+
+#[derive(Resource)]
+struct SettingsHandle(Handle<Settings>);
+
+// ----------------------------------<< Loaders >>----------------------------------
+
+#[derive(Default)]
+pub struct SettingsLoader;
+
+impl AssetLoader for SettingsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let settings: Settings = ron::de::from_bytes(bytes)?;
+
+            load_context.set_default_asset(LoadedAsset::new(settings));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["settings.ron"]
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// The file was already read when the plugin was built, so only edits after that are applied
+fn reload_settings(
+    mut asset_events: EventReader<AssetEvent<Settings>>,
+    settings_assets: Res<Assets<Settings>>,
+    settings_handle: Option<Res<SettingsHandle>>,
+    overrides: Res<SettingsOverrides>,
+    mut settings: ResMut<Settings>
+) {
+    let Some(settings_handle) = settings_handle else { return };
+
+    for event in asset_events.iter() {
+        let AssetEvent::Modified { handle } = event else { continue };
+        if *handle != settings_handle.0 {
+            continue;
+        }
+
+        if let Some(reloaded) = settings_assets.get(handle) {
+            let reloaded = reloaded.clone().with_overrides(&overrides.0).validated();
+
+            if *settings != reloaded {
+                info!("Reloaded settings");
+                *settings = reloaded;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn max_speed(max_speed: &str) -> f32 {
+        let overrides = [("player.max_speed".to_string(), max_speed.to_string())];
+        Settings::default().with_overrides(&overrides).validated().player.max_speed
+    }
+
+    #[test]
+    fn max_speed_stays_below_light_speed() {
+        assert_eq!(max_speed("1500"), 1500.0);
+        assert_eq!(max_speed(&SPEED_OF_LIGHT.to_string()), PLAYER_MAX_SPEED);
+        assert_eq!(max_speed("1e9"), PLAYER_MAX_SPEED);
+        assert_eq!(max_speed("-10"), 0.0);
+    }
+}
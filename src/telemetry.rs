@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf
+};
+
+use super::*;
+
+// Records the player's clocks and motion to a file while the game runs, e.g.
+//
+//     cargo run -- --telemetry run.csv --telemetry-rate 10
+//
+// A .jsonl file gets one JSON object per line, anything else but .json is written as CSV. A .json
+// file isn't recorded, since a file of lines wouldn't be valid JSON.
+// The rate is in samples per second of coordinate time, 0 records every physics step
+
+const TELEMETRY_DEFAULT_RATE: f32 = 10.0;
+
+pub struct TelemetryPlugin {
+    pub output: Option<PathBuf>,
+    pub rate: f32
+}
+
+impl TelemetryPlugin {
+    // Reads --telemetry <file> and --telemetry-rate <samples per second>
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let mut plugin = TelemetryPlugin { output: None, rate: TELEMETRY_DEFAULT_RATE };
+        let mut args = args.skip(1);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--telemetry" => plugin.output = args.next().map(PathBuf::from),
+                "--telemetry-rate" => match args.next().map(|rate| rate.parse::<f32>()) {
+                    Some(Ok(rate)) if rate >= 0.0 => plugin.rate = rate,
+                    _ => warn!("--telemetry-rate needs a number of samples per second, using {}", plugin.rate)
+                },
+                _ => {}
+            }
+        }
+
+        plugin
+    }
+}
+
+impl Plugin for TelemetryPlugin {
+    fn build(&self, app: &mut App) {
+        let telemetry = match &self.output {
+            Some(output) => Telemetry::create(output, self.rate),
+            None => Telemetry::default()
+        };
+
+        app
+            .insert_resource(telemetry)
+            .add_system_set_to_stage(PhysicsStage::Step, SystemSet::new()
+                .with_run_criteria(PhysicsSteps)
+                .with_system(record_telemetry.after(PhysicsSystem::Move)));
+    }
+}
+
+// ----------------------------------<< Resources >>----------------------------------
+
+#[derive(Resource)]
+pub struct Telemetry {
+    writer: Option<BufWriter<File>>,
+    format: TelemetryFormat,
+    rate: f32,
+    next_sample_time: f32,
+    // Coordinate time of the latest sample, to notice it going back
+    last_sample_time: Option<f32>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryFormat {
+    Csv,
+    JsonLines
+}
+
+// One line of the output, times in seconds, distances in pixels and speed as a fraction of c
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct TelemetrySample {
+    pub global_time: f32,
+    pub local_time: f32,
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub speed: f32,
+    pub gamma: f32
+}
+
+// Records nothing
+impl Default for Telemetry {
+    fn default() -> Self {
+        Telemetry { writer: None, format: TelemetryFormat::Csv, rate: TELEMETRY_DEFAULT_RATE, next_sample_time: 0.0, last_sample_time: None }
+    }
+}
+
+impl Telemetry {
+    // Falls back to recording nothing if the file can't be created, the game is still playable
+    pub fn create(output: &std::path::Path, rate: f32) -> Self {
+        let format = match output.extension().and_then(|ext| ext.to_str()) {
+            Some("jsonl") => TelemetryFormat::JsonLines,
+            Some("json") => {
+                error!("Not recording telemetry to {:?}, it would be JSON Lines rather than JSON. Use .jsonl instead", output);
+                return Telemetry { rate, ..Telemetry::default() };
+            },
+            _ => TelemetryFormat::Csv
+        };
+
+        let mut telemetry = Telemetry { format, rate, ..Telemetry::default() };
+
+        match File::create(output) {
+            Ok(file) => {
+                let mut writer = BufWriter::new(file);
+
+                if format == TelemetryFormat::Csv {
+                    if let Err(err) = writeln!(writer, "global_time,local_time,x,y,vx,vy,speed,gamma") {
+                        error!("Couldn't write telemetry to {:?}: {}", output, err);
+                        return telemetry;
+                    }
+                }
+
+                info!("Recording telemetry to {:?}", output);
+                telemetry.writer = Some(writer);
+            },
+            Err(err) => error!("Couldn't create telemetry file {:?}: {}", output, err)
+        }
+
+        telemetry
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    // Lines are flushed straight away since the window closing can end the process without
+    // dropping the writer
+    fn write(&mut self, sample: &TelemetrySample) -> std::io::Result<()> {
+        let Some(writer) = self.writer.as_mut() else { return Ok(()) };
+
+        match self.format {
+            TelemetryFormat::Csv => writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                sample.global_time, sample.local_time, sample.x, sample.y, sample.vx, sample.vy, sample.speed, sample.gamma
+            )?,
+            TelemetryFormat::JsonLines => {
+                serde_json::to_writer(&mut *writer, sample)?;
+                writeln!(writer)?;
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+// Runs in the physics step after the player has moved, so samples line up with the simulation
+fn record_telemetry(
+    mut telemetry: ResMut<Telemetry>,
+    global_time: Res<GlobalTime>,
+    local_time: Res<LocalTime>,
+    query_player: Query<(&Position, &Velocity), With<Player>>
+) {
+    if !telemetry.is_recording() {
+        return;
+    }
+
+    // Coordinate time went back, e.g. a level restarted, a save was loaded or a replay was
+    // scrubbed, so sampling starts over from there rather than waiting to catch up
+    if telemetry.last_sample_time.is_some_and(|last_sample_time| global_time.0 < last_sample_time) {
+        telemetry.next_sample_time = global_time.0;
+    }
+
+    if global_time.0 < telemetry.next_sample_time {
+        return;
+    }
+
+    let (Position(position), Velocity(velocity)) = query_player.single();
+    let speed = velocity.length() / SPEED_OF_LIGHT;
+
+    let sample = TelemetrySample {
+        global_time: global_time.0,
+        local_time: local_time.0,
+        x: position.x,
+        y: position.y,
+        vx: velocity.x,
+        vy: velocity.y,
+        speed,
+        gamma: 1.0 / (1.0 - speed.powi(2)).sqrt()
+    };
+
+    if let Err(err) = telemetry.write(&sample) {
+        error!("Stopped recording telemetry: {}", err);
+        telemetry.writer = None;
+        return;
+    }
+
+    telemetry.last_sample_time = Some(global_time.0);
+
+    if telemetry.rate > 0.0 {
+        // Stepping from the last threshold rather than the current time keeps the rate exact,
+        // unless the physics step is longer than the interval
+        telemetry.next_sample_time += 1.0 / telemetry.rate;
+
+        if telemetry.next_sample_time <= global_time.0 {
+            telemetry.next_sample_time = global_time.0 + 1.0 / telemetry.rate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The global times sampled while the clock goes through `global_times`, one step each
+    fn sampled_times(name: &str, global_times: &[f32]) -> Vec<f32> {
+        let file = std::env::temp_dir().join(name);
+        let mut world = World::new();
+        world.insert_resource(Telemetry::create(&file, 10.0));
+        world.insert_resource(GlobalTime(0.0));
+        world.insert_resource(LocalTime(0.0));
+        world.spawn((Player, Position(Vec2::ZERO), Velocity(Vec2::ZERO)));
+
+        let mut stage = SystemStage::single(record_telemetry);
+
+        for &global_time in global_times {
+            world.resource_mut::<GlobalTime>().0 = global_time;
+            stage.run(&mut world);
+        }
+
+        drop(world);
+        let text = std::fs::read_to_string(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        text.lines().skip(1).map(|line| line.split(',').next().unwrap().parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn samples_start_over_when_time_goes_back() {
+        let steps: Vec<f32> = (0..=10).chain(0..=3).map(|step| step as f32 * 0.05).collect();
+
+        assert_eq!(sampled_times("spacetime_game_rewound.csv", &steps), vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.0, 0.1]);
+    }
+
+    #[test]
+    fn json_isnt_recorded() {
+        let file = std::env::temp_dir().join("spacetime_game_telemetry.json");
+
+        assert!(!Telemetry::create(&file, 10.0).is_recording());
+        assert!(!file.exists());
+    }
+}
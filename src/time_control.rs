@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use super::*;
+
+// Pauses, slows down and speeds up the physics. Steps are always TIMESTEP long, the speed only
+// changes how many are taken each second on the wall clock, so the player's coordinate and proper
+// time go on being worked out from the same steps, and come out the same, at any speed
+//
+// Controls:
+//   pause         pause/resume, F8 or Start by default, see src/bindings.rs
+//   F10           take a single physics step, pausing first if need be
+//   [ ]           halve/double the speed
+//   Backslash     back to normal speed
+
+const TIME_MIN_SPEED: f32 = 0.125;
+const TIME_MAX_SPEED: f32 = 8.0;
+
+pub struct TimeControlPlugin;
+
+impl Plugin for TimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(control_time);
+    }
+}
+
+// ----------------------------------<< Systems >>----------------------------------
+
+fn control_time(keyboard_input: Res<Input<KeyCode>>, actions: Res<Actions>, mut physics_clock: ResMut<PhysicsClock>) {
+    if actions.pause {
+        physics_clock.paused = !physics_clock.paused;
+        info!("{} the physics", if physics_clock.paused { "Paused" } else { "Resumed" });
+    }
+
+    // For watching what the reorient systems make of each step
+    if keyboard_input.just_pressed(KeyCode::F10) {
+        physics_clock.paused = true;
+        physics_clock.extra_steps += 1;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::LBracket) {
+        physics_clock.speed = (physics_clock.speed / 2.0).max(TIME_MIN_SPEED);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::RBracket) {
+        physics_clock.speed = (physics_clock.speed * 2.0).min(TIME_MAX_SPEED);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Backslash) {
+        physics_clock.speed = 1.0;
+    }
+}
@@ -0,0 +1,386 @@
+use bevy::prelude::*;
+
+use super::*;
+
+// A worldline given as a function of coordinate time (in seconds) rather than as samples.
+// Closures `Fn(f64) -> Vec2` are trajectories too, so captured parameters can be used
+pub trait Trajectory: Send + Sync + 'static {
+    fn position(&self, time: f64) -> Vec2;
+
+    // Central difference by default, override it when the derivative is known
+    fn velocity(&self, time: f64) -> Vec2 {
+        const STEP: f64 = 1e-3;
+
+        (self.position(time + STEP) - self.position(time - STEP)) / (2.0 * STEP) as f32
+    }
+
+    // The coordinate time (in seconds) where the past light cone of an observer at
+    // observer_position and global_time crosses the worldline, if it ever does. Something that
+    // has been accelerating forever can have moved away ahead of its own light
+    fn past_light_cone_time(&self, observer_position: Vec2, global_time: f64) -> Option<f64> {
+        past_light_cone_time_numeric(self, observer_position, global_time)
+    }
+
+    // The proper time (in seconds) a clock riding on the trajectory has ticked through since the
+    // coordinate time 0, negative before it. Integrated numerically by default
+    fn proper_time(&self, time: f64) -> f64 {
+        proper_time_numeric(self, time)
+    }
+}
+
+impl<F: Fn(f64) -> Vec2 + Send + Sync + 'static> Trajectory for F {
+    fn position(&self, time: f64) -> Vec2 {
+        self(time)
+    }
+}
+
+// Works for any timelike trajectory: steps back in time until the light cone is bracketed and
+// then solves in between
+pub fn past_light_cone_time_numeric<T: Trajectory + ?Sized>(trajectory: &T, observer_position: Vec2, global_time: f64) -> Option<f64> {
+    let gap = |time: f64| {
+        let offset = trajectory.position(time) - observer_position;
+        let distance = offset.length();
+        let slope = -(SPEED_OF_LIGHT as f64)
+            - if distance > 0.0 { (offset.dot(trajectory.velocity(time)) / distance) as f64 } else { 0.0 };
+
+        (SPEED_OF_LIGHT as f64 * (global_time - time) - distance as f64, slope)
+    };
+
+    let mut step = ((trajectory.position(global_time) - observer_position).length() / SPEED_OF_LIGHT) as f64 + 1e-3;
+
+    for _ in 0..64 {
+        let low = global_time - step;
+        if gap(low).0 >= 0.0 {
+            return Some(find_light_cone_root(gap, low, global_time));
+        }
+
+        step *= 2.0;
+    }
+
+    None
+}
+
+// The integral of 1/gamma over coordinate time from 0, by Simpson's rule
+pub fn proper_time_numeric<T: Trajectory + ?Sized>(trajectory: &T, time: f64) -> f64 {
+    const STEPS: usize = 256;
+
+    let step = time / STEPS as f64;
+
+    let sum: f64 = (0..=STEPS).map(|i| {
+        let weight = if i == 0 || i == STEPS { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+        weight * inverse_gamma(trajectory.velocity(i as f64 * step).length())
+    }).sum();
+
+    sum * step / 3.0
+}
+
+fn inverse_gamma(speed: f32) -> f64 {
+    (1.0 - (speed as f64 / SPEED_OF_LIGHT as f64).powi(2)).max(0.0).sqrt()
+}
+
+// ----------------------------------<< Trajectories >>----------------------------------
+
+// Constant velocity through `position` at `time`
+pub struct Line {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub time: f64
+}
+
+impl Trajectory for Line {
+    fn position(&self, time: f64) -> Vec2 {
+        self.position + self.velocity * (time - self.time) as f32
+    }
+
+    fn velocity(&self, _time: f64) -> Vec2 {
+        self.velocity
+    }
+
+    // |r + vt| = c(T - t) squared is a quadratic in t, the root before T is on the past light cone
+    fn past_light_cone_time(&self, observer_position: Vec2, global_time: f64) -> Option<f64> {
+        let c2 = (SPEED_OF_LIGHT as f64).powi(2);
+        let r = (self.position - observer_position).as_dvec2() - self.velocity.as_dvec2() * self.time;
+        let v = self.velocity.as_dvec2();
+
+        let a = v.length_squared() - c2;
+        let b = 2.0 * (r.dot(v) + c2 * global_time);
+        let c = r.length_squared() - c2 * global_time.powi(2);
+
+        // a < 0 for anything slower than light, so this is the smaller root
+        Some((-b + (b.powi(2) - 4.0 * a * c).max(0.0).sqrt()) / (2.0 * a))
+    }
+
+    fn proper_time(&self, time: f64) -> f64 {
+        time * inverse_gamma(self.velocity.length())
+    }
+}
+
+// Uniform circular motion, at angle `phase` when the time is 0
+pub struct Circle {
+    pub center: Vec2,
+    pub radius: f32,
+    pub period: f32,
+    pub phase: f32
+}
+
+impl Circle {
+    fn angle(&self, time: f64) -> f64 {
+        self.phase as f64 + std::f64::consts::TAU * time / self.period as f64
+    }
+}
+
+impl Trajectory for Circle {
+    fn position(&self, time: f64) -> Vec2 {
+        let angle = self.angle(time);
+
+        self.center + self.radius * Vec2::new(angle.cos() as f32, angle.sin() as f32)
+    }
+
+    fn velocity(&self, time: f64) -> Vec2 {
+        let angle = self.angle(time);
+        let speed = std::f32::consts::TAU * self.radius / self.period;
+
+        speed * Vec2::new(-angle.sin() as f32, angle.cos() as f32)
+    }
+
+    // The distance to the observer is always within a radius of the distance to the center,
+    // which brackets the light cone tightly without searching for it
+    fn past_light_cone_time(&self, observer_position: Vec2, global_time: f64) -> Option<f64> {
+        let distance = (self.center - observer_position).length();
+        let low = global_time - ((distance + self.radius) / SPEED_OF_LIGHT) as f64;
+        let high = global_time - ((distance - self.radius).max(0.0) / SPEED_OF_LIGHT) as f64;
+
+        Some(find_light_cone_root(|time| {
+            let offset = self.position(time) - observer_position;
+            let distance = offset.length();
+            let slope = -(SPEED_OF_LIGHT as f64)
+                - if distance > 0.0 { (offset.dot(self.velocity(time)) / distance) as f64 } else { 0.0 };
+
+            (SPEED_OF_LIGHT as f64 * (global_time - time) - distance as f64, slope)
+        }, low, high))
+    }
+
+    fn proper_time(&self, time: f64) -> f64 {
+        time * inverse_gamma(std::f32::consts::TAU * self.radius / self.period)
+    }
+}
+
+// Constant proper acceleration along `direction` (hyperbolic motion), passing through `position`
+// at `time` with the signed speed `speed` along `direction`
+pub struct UniformAcceleration {
+    pub position: Vec2,
+    pub speed: f32,
+    pub direction: Vec2,
+    pub acceleration: f32,
+    pub time: f64
+}
+
+impl UniformAcceleration {
+    // Without any acceleration there's no hyperbola, and everything below would divide by zero
+    fn coasting(&self) -> Option<Line> {
+        (self.acceleration == 0.0).then(|| Line {
+            position: self.position,
+            velocity: self.direction.normalize_or_zero() * self.speed,
+            time: self.time
+        })
+    }
+
+    // Time since the moment the body would have been at rest
+    fn time_since_rest(&self, time: f64) -> f64 {
+        let beta = (self.speed / SPEED_OF_LIGHT) as f64;
+
+        time - self.time + beta / (1.0 - beta.powi(2)).sqrt() * SPEED_OF_LIGHT as f64 / self.acceleration as f64
+    }
+
+    // Distance along direction from the centre of the hyperbola, which is c²/a behind where the
+    // body would have been at rest
+    fn distance_from_rest(&self, time: f64) -> f64 {
+        let c = SPEED_OF_LIGHT as f64;
+        let a = self.acceleration as f64;
+
+        c.powi(2) / a * (1.0 + (a * self.time_since_rest(time) / c).powi(2)).sqrt()
+    }
+}
+
+impl Trajectory for UniformAcceleration {
+    fn position(&self, time: f64) -> Vec2 {
+        if let Some(line) = self.coasting() {
+            return line.position(time);
+        }
+
+        let travelled = self.distance_from_rest(time) - self.distance_from_rest(self.time);
+
+        self.position + self.direction.normalize_or_zero() * travelled as f32
+    }
+
+    fn velocity(&self, time: f64) -> Vec2 {
+        if let Some(line) = self.coasting() {
+            return line.velocity(time);
+        }
+
+        let c = SPEED_OF_LIGHT as f64;
+        let a = self.acceleration as f64;
+        let s = self.time_since_rest(time);
+
+        self.direction.normalize_or_zero() * (a * s / (1.0 + (a * s / c).powi(2)).sqrt()) as f32
+    }
+
+    // With Y the distance from the centre of the hyperbola, Y² - c²s² = c⁴/a² turns the squared
+    // light cone equation into one that is linear in Y and s. Substituting it back in leaves a
+    // quadratic in s, whose roots are checked against the unsquared equation
+    fn past_light_cone_time(&self, observer_position: Vec2, global_time: f64) -> Option<f64> {
+        if let Some(line) = self.coasting() {
+            return line.past_light_cone_time(observer_position, global_time);
+        }
+
+        let c = SPEED_OF_LIGHT as f64;
+        let a = self.acceleration as f64;
+        let direction = self.direction.normalize_or_zero().as_dvec2();
+
+        let offset = (self.position - observer_position).as_dvec2();
+        let along = offset.dot(direction) - self.distance_from_rest(self.time);
+        let across = offset.length_squared() - offset.dot(direction).powi(2);
+        let observer_time = self.time_since_rest(global_time);
+
+        if along.abs() > 1e-6 && a > 0.0 {
+            let alpha = (c.powi(2) * observer_time.powi(2) - along.powi(2) - c.powi(4) / a.powi(2) - across) / (2.0 * along);
+            let beta = -c.powi(2) * observer_time / along;
+
+            let qa = beta.powi(2) - c.powi(2);
+            let qb = 2.0 * alpha * beta;
+            let qc = alpha.powi(2) - c.powi(4) / a.powi(2);
+            let discriminant = qb.powi(2) - 4.0 * qa * qc;
+
+            if discriminant >= 0.0 && qa.abs() > 1e-12 {
+                let best = [(-qb + discriminant.sqrt()) / (2.0 * qa), (-qb - discriminant.sqrt()) / (2.0 * qa)]
+                    .into_iter()
+                    .filter(|&s| s <= observer_time && alpha + beta * s > 0.0)
+                    .map(|s| s - observer_time + global_time)
+                    .filter(|&time| {
+                        let distance = (self.position(time) - observer_position).length() as f64;
+                        (c * (global_time - time) - distance).abs() < 1e-2 * (1.0 + distance)
+                    })
+                    .reduce(f64::max);
+
+                if best.is_some() {
+                    return best;
+                }
+            }
+        }
+
+        past_light_cone_time_numeric(self, observer_position, global_time)
+    }
+
+    // The rapidity grows linearly with proper time, a s / c = sinh(a τ / c)
+    fn proper_time(&self, time: f64) -> f64 {
+        if let Some(line) = self.coasting() {
+            return line.proper_time(time);
+        }
+
+        let c = SPEED_OF_LIGHT as f64;
+        let a = self.acceleration as f64;
+        let rapidity = |time: f64| (a * self.time_since_rest(time) / c).asinh();
+
+        c / a * (rapidity(time) - rapidity(0.0))
+    }
+}
+
+// ----------------------------------<< Components >>----------------------------------
+
+// A body following a Trajectory, its light cone crossing is solved on the exact worldline
+// instead of between samples like a Path
+#[derive(Component)]
+pub struct AnalyticPath(pub Box<dyn Trajectory>);
+
+impl AnalyticPath {
+    pub fn new(trajectory: impl Trajectory) -> Self {
+        AnalyticPath(Box::new(trajectory))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Positions are f32, so distances are only good to about this fraction
+    const DISTANCE_TOLERANCE: f64 = 1e-5;
+    // Bodies closing in at nearly light speed turn tiny errors in distance into bigger ones in time,
+    // so times are compared against how long ago the light left
+    const TIME_TOLERANCE: f64 = 1e-4;
+
+    const OBSERVERS: [(f32, f32); 4] = [(0.0, 0.0), (3_000.0, -1_500.0), (-20_000.0, 500.0), (150.0, 40_000.0)];
+    const TIMES: [f64; 4] = [0.0, 2.5, 17.0, 120.0];
+
+    fn check_against_numeric(trajectory: &impl Trajectory) {
+        for (x, y) in OBSERVERS {
+            for global_time in TIMES {
+                let observer_position = Vec2::new(x, y);
+                let exact = trajectory.past_light_cone_time(observer_position, global_time);
+                let numeric = past_light_cone_time_numeric(trajectory, observer_position, global_time);
+
+                let (Some(exact), Some(numeric)) = (exact, numeric) else {
+                    assert_eq!(exact.is_some(), numeric.is_some(), "observer at {:?} at {}s", observer_position, global_time);
+                    continue;
+                };
+
+                let distance = (trajectory.position(exact) - observer_position).length() as f64;
+                let gap = SPEED_OF_LIGHT as f64 * (global_time - exact) - distance;
+
+                assert!(
+                    gap.abs() < DISTANCE_TOLERANCE * (1.0 + distance),
+                    "observer at {:?} at {}s: exact {} is {} off the light cone", observer_position, global_time, exact, gap
+                );
+                assert!(
+                    (exact - numeric).abs() < TIME_TOLERANCE * (1.0 + global_time - numeric),
+                    "observer at {:?} at {}s: exact {} but numeric {}", observer_position, global_time, exact, numeric
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn line_matches_numeric() {
+        check_against_numeric(&Line { position: Vec2::new(-500.0, 200.0), velocity: Vec2::new(1_800.0, -900.0), time: 3.0 });
+        check_against_numeric(&Line { position: Vec2::new(1_000.0, 0.0), velocity: Vec2::ZERO, time: 0.0 });
+    }
+
+    #[test]
+    fn circle_matches_numeric() {
+        check_against_numeric(&Circle { center: Vec2::new(800.0, -300.0), radius: 1_500.0, period: 4.0, phase: 1.0 });
+        check_against_numeric(&Circle { center: Vec2::ZERO, radius: 100.0, period: 30.0, phase: 0.0 });
+    }
+
+    #[test]
+    fn uniform_acceleration_matches_numeric() {
+        check_against_numeric(&UniformAcceleration {
+            position: Vec2::new(0.0, 1_000.0),
+            speed: 0.0,
+            direction: Vec2::new(1.0, 0.0),
+            acceleration: 400.0,
+            time: 0.0
+        });
+        check_against_numeric(&UniformAcceleration {
+            position: Vec2::new(-2_000.0, -2_000.0),
+            speed: -2_400.0,
+            direction: Vec2::new(1.0, 1.0),
+            acceleration: 1_000.0,
+            time: 5.0
+        });
+        check_against_numeric(&UniformAcceleration {
+            position: Vec2::new(500.0, 0.0),
+            speed: 1_200.0,
+            direction: Vec2::new(0.0, 1.0),
+            acceleration: 0.0,
+            time: 2.0
+        });
+    }
+
+    #[test]
+    fn uniform_acceleration_without_acceleration_coasts() {
+        let coasting = UniformAcceleration { position: Vec2::ZERO, speed: 2_400.0, direction: Vec2::X, acceleration: 0.0, time: 0.0 };
+
+        assert_eq!(coasting.position(2.0), Vec2::new(4_800.0, 0.0));
+        assert_eq!(coasting.velocity(2.0), Vec2::new(2_400.0, 0.0));
+        assert!((coasting.proper_time(10.0) - 8.0).abs() < 1e-9, "proper time {}", coasting.proper_time(10.0));
+    }
+}
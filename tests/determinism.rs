@@ -0,0 +1,94 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use spacetime_game::*;
+use std::time::{Duration, Instant};
+
+const SCRIPT: &str = "assets/scripts/ship_controls.ron";
+
+// Steps to compare, 20 seconds of proper time
+const STEPS: usize = 1200;
+
+// Frames on the wall clock, for the runs with a fixed timestep
+const FRAME: f32 = 1.0 / 60.0;
+
+fn physics_app(fixed_timestep: bool) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins)
+        .add_plugin(SettingsPlugin { live_reload: false, ..SettingsPlugin::from_args(std::iter::empty()) })
+        .add_plugin(SpacetimePhysicsPlugin { fixed_timestep });
+
+    app
+}
+
+// The script plays on every physics step, so that it's the same however the steps fall on frames
+fn scripted_app(fixed_timestep: bool) -> App {
+    let mut app = physics_app(fixed_timestep);
+    app
+        .add_plugin(BindingsPlugin { file: None, live_reload: false })
+        .add_plugin(HeadlessPlugin::from_file(SCRIPT).unwrap());
+
+    app
+}
+
+fn run_script() -> Worldline {
+    let mut app = scripted_app(false);
+
+    // Headless, every update is a step
+    for _ in 0..STEPS {
+        app.update();
+    }
+
+    app.world.remove_resource::<Worldline>().unwrap()
+}
+
+// On a wall clock that ticks exactly FRAME every update, however fast the machine is
+fn run_at_speed(speed: f32) -> Worldline {
+    let mut app = scripted_app(true);
+    app.world.resource_mut::<PhysicsClock>().speed = speed;
+
+    let start = Instant::now();
+    let mut frames = 0;
+
+    while app.world.resource::<Worldline>().0.len() < STEPS {
+        assert!(frames < 100 * STEPS, "Only {} steps after {} frames", app.world.resource::<Worldline>().0.len(), frames);
+
+        frames += 1;
+        app.insert_resource(TimeUpdateStrategy::ManualInstant(start + Duration::from_secs_f32(frames as f32 * FRAME)));
+        app.update();
+    }
+
+    // Fast runs take several steps a frame, and can go past
+    let mut worldline = app.world.remove_resource::<Worldline>().unwrap();
+    worldline.0.truncate(STEPS);
+    worldline
+}
+
+#[test]
+fn same_script_same_worldline() {
+    let first = run_script();
+    let second = run_script();
+
+    assert_eq!(first.0.len(), STEPS);
+    assert!(first == second, "The runs split apart");
+
+    // Aimed straight away, then turned left for half a second
+    let aimed = first.0[1].heading;
+    let turned = first.0.last().unwrap().heading;
+    assert!((aimed - 1.0).abs() < 1e-6, "aimed at {}", aimed);
+    assert!((turned - aimed - 0.5 * Settings::default().player.turn_rate).abs() < 0.1, "turned to {}", turned);
+}
+
+#[test]
+fn speed_doesnt_change_the_worldline() {
+    let slow = run_at_speed(0.125);
+    let fast = run_at_speed(8.0);
+
+    assert_eq!(slow.0.len(), STEPS);
+
+    if let Some(step) = slow.0.iter().zip(fast.0.iter()).position(|(slow, fast)| slow != fast) {
+        panic!("The runs split apart at step {}: {:?} and {:?}", step, slow.0[step], fast.0[step]);
+    }
+
+    // The script did move the player
+    assert!(slow.0.last().unwrap().global_time > slow.0.last().unwrap().local_time);
+}
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use spacetime_game::*;
+
+const SCRIPT: &str = "assets/scripts/out_and_back.ron";
+
+// The same app as `cargo run -- --headless`, with the settings the game ships with, updated until
+// the script runs out
+fn run_script(headless: HeadlessPlugin) -> App {
+    let duration = headless.script.duration;
+
+    let mut app = App::new();
+    app
+        .add_plugins(MinimalPlugins)
+        .add_plugin(SettingsPlugin { live_reload: false, ..SettingsPlugin::from_args(std::iter::empty()) })
+        .add_plugin(SpacetimePhysicsPlugin { fixed_timestep: false })
+        .add_plugin(BindingsPlugin { file: None, live_reload: false })
+        .add_plugin(headless);
+
+    // A step an update, with plenty to spare
+    let most_updates = (2.0 * duration / TIMESTEP) as usize;
+    for _ in 0..most_updates {
+        app.update();
+
+        if app.world.resource::<LocalTime>().0 >= duration {
+            return app;
+        }
+    }
+
+    panic!("The script didn't finish within {} updates", most_updates);
+}
+
+#[test]
+fn out_and_back_ends_where_it_always_has() {
+    let app = run_script(HeadlessPlugin::from_file(SCRIPT).unwrap());
+
+    let global_time = app.world.resource::<GlobalTime>().0;
+    let local_time = app.world.resource::<LocalTime>().0;
+    let worldline = app.world.resource::<Worldline>();
+    let last = worldline.0.last().unwrap();
+
+    assert!((global_time - 22.3522).abs() < 1e-3, "global time {}", global_time);
+    assert!((local_time - 20.0165).abs() < 1e-3, "local time {}", local_time);
+    assert!((last.position - Vec2::new(-993.122, 0.0)).length() < 1e-2, "position {}", last.position);
+    assert!((last.velocity - Vec2::new(2000.0, 0.0)).length() < 1e-2, "velocity {}", last.velocity);
+}
+
+// A twin left at rest ages by the coordinate time, the traveller by less, and by exactly as much
+// less as time dilation says for every step it took
+#[test]
+fn out_and_back_ages_less_than_its_twin() {
+    let app = run_script(HeadlessPlugin::from_file(SCRIPT).unwrap());
+
+    let global_time = app.world.resource::<GlobalTime>().0;
+    let local_time = app.world.resource::<LocalTime>().0;
+    let worldline = app.world.resource::<Worldline>();
+
+    assert!(local_time < global_time - 2.0, "local time {} against global time {}", local_time, global_time);
+
+    let mut dilated = 0.0;
+    for step in worldline.0.windows(2) {
+        let (before, after) = (&step[0], &step[1]);
+        let gamma = 1.0 / (1.0 - after.velocity.length_squared() / SPEED_OF_LIGHT.powi(2)).sqrt();
+
+        assert!((after.local_time - before.local_time - TIMESTEP).abs() < 1e-4);
+        assert!(((after.global_time - before.global_time) - TIMESTEP * gamma).abs() < 1e-4);
+
+        dilated += (after.global_time - before.global_time) - (after.local_time - before.local_time);
+    }
+
+    assert!((global_time - local_time - dilated).abs() < 1e-2, "{} apart, steps add up to {}", global_time - local_time, dilated);
+}
+
+#[test]
+fn malformed_script_is_an_error() {
+    let path = std::env::temp_dir().join("spacetime_game_malformed.ron");
+    std::fs::write(&path, "(duration: 20.0, inputs: [(time: 0.0, press: [Z])])").unwrap();
+
+    let result = HeadlessPlugin::from_file(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    let err = result.err().expect("Z isn't a scripted key");
+    assert!(err.contains("Couldn't parse input script"), "{}", err);
+}